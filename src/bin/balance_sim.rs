@@ -0,0 +1,62 @@
+//! Headless balance simulator, bots play seeded runs as fast as possible and the stats are written as CSV.
+//!
+//! `cargo run --bin balance_sim -- --runs 1000 --strategy gun_only --seed 42 --out runs.csv`
+//!
+//! Entity pooling can be benchmarked by running the same seeds with and without `--no-pooling`,
+//! the spawned entity counts and the wall clock time are logged at the end:
+//!
+//! `cargo run --release --bin balance_sim -- --runs 20 --strategy gun_only --out /dev/null [--no-pooling]`
+
+use std::{path::PathBuf, process::ExitCode};
+
+use bevy::prelude::*;
+use porcle::{
+    HeadlessAppPlugin,
+    sim::{BotStrategy, SimConfig, SimPlugin},
+};
+
+const USAGE: &str = "usage: balance_sim [--runs N] [--strategy balanced|gun_only|ball_only] \
+                     [--seed N] [--max-run-s SECONDS] [--out FILE] [--no-pooling]";
+
+fn main() -> ExitCode {
+    let config = match parse_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match App::new()
+        .add_plugins((HeadlessAppPlugin, SimPlugin(config)))
+        .run()
+    {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(_) => ExitCode::FAILURE,
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<SimConfig, String> {
+    let mut config = SimConfig::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value of {arg}"));
+        match arg.as_str() {
+            "--runs" => config.runs = value()?.parse().map_err(|_| "invalid --runs")?,
+            "--strategy" => {
+                let name = value()?;
+                config.strategy = BotStrategy::ALL
+                    .into_iter()
+                    .find(|s| s.name() == name)
+                    .ok_or(format!("unknown strategy {name}"))?;
+            }
+            "--seed" => config.seed = value()?.parse().map_err(|_| "invalid --seed")?,
+            "--max-run-s" => {
+                config.max_run_s = value()?.parse().map_err(|_| "invalid --max-run-s")?;
+            }
+            "--out" => config.output = Some(PathBuf::from(value()?)),
+            "--no-pooling" => config.pooling = false,
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+    Ok(config)
+}
@@ -0,0 +1,327 @@
+//! Achievements unlocked by gameplay and saved locally.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    screen::{Screen, in_game_state},
+    theme::palette::{COL_BULLET, LABEL_TEXT, NODE_BG},
+};
+
+use super::{
+    ball::{BALL_MAX_SPEED, BallCaptured, BallReflected, BallSpeed},
+    core::TakeDamage,
+    mode::ModeClock,
+    progression::progress_enabled,
+    spawn::{
+        ball::Ball,
+        enemy::{EnemyKilled, KillSource},
+    },
+    stats::RunStats,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Achievements>()
+        .init_resource::<AchievementProgress>()
+        .add_event::<AchievementUnlocked>()
+        .add_systems(Startup, spawn_toast_container)
+        .add_systems(OnEnter(Screen::Game), reset_progress)
+        .add_systems(
+            Update,
+            (track_progress, unlock_achievements)
+                .chain()
+                .run_if(in_game_state.and(progress_enabled)),
+        )
+        .add_systems(Update, (spawn_toasts, fade_toasts));
+}
+
+const TOAST_DURATION_S: f32 = 3.5;
+const TOAST_FADE_S: f32 = 0.3;
+
+/// Saved by the snake case name, variants must never be renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    FirstBlood,
+    Exterminator,
+    LoneSaw,
+    Survivor,
+    SpeedDemon,
+    Pacifist,
+    Untouchable,
+    ReturnToSender,
+    Catcher,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 9] = [
+        Achievement::FirstBlood,
+        Achievement::Exterminator,
+        Achievement::LoneSaw,
+        Achievement::Survivor,
+        Achievement::SpeedDemon,
+        Achievement::Pacifist,
+        Achievement::Untouchable,
+        Achievement::ReturnToSender,
+        Achievement::Catcher,
+    ];
+
+    pub fn def(&self) -> AchievementDef {
+        match self {
+            Achievement::FirstBlood => AchievementDef {
+                name: "FIRST BLOOD",
+                description: "KILL AN ENEMY",
+                goal: AchievementGoal::Kills(1),
+            },
+            Achievement::Exterminator => AchievementDef {
+                name: "EXTERMINATOR",
+                description: "KILL 200 ENEMIES IN A SINGLE RUN",
+                goal: AchievementGoal::Kills(200),
+            },
+            Achievement::LoneSaw => AchievementDef {
+                name: "LONE SAW",
+                description: "KILL 10 ENEMIES WITHOUT THE SAWBLADE RETURNING",
+                goal: AchievementGoal::KillsWithoutBallReturning(10),
+            },
+            Achievement::Survivor => AchievementDef {
+                name: "SURVIVOR",
+                description: "SURVIVE 5 MINUTES",
+                goal: AchievementGoal::Survive(300.),
+            },
+            Achievement::SpeedDemon => AchievementDef {
+                name: "SPEED DEMON",
+                description: "REACH MAX SAWBLADE SPEED",
+                goal: AchievementGoal::MaxBallSpeed,
+            },
+            Achievement::Pacifist => AchievementDef {
+                name: "PACIFIST",
+                description: "WIN WITHOUT FIRING A SHOT",
+                goal: AchievementGoal::WinWithoutFiring,
+            },
+            Achievement::Untouchable => AchievementDef {
+                name: "UNTOUCHABLE",
+                description: "GO 2 MINUTES WITHOUT THE CORE GETTING HIT",
+                goal: AchievementGoal::Untouched(120.),
+            },
+            Achievement::ReturnToSender => AchievementDef {
+                name: "RETURN TO SENDER",
+                description: "KILL 5 ENEMIES WITH PARRIED PROJECTILES IN A SINGLE RUN",
+                goal: AchievementGoal::KillsBy(KillSource::ReflectedProjectile, 5),
+            },
+            Achievement::Catcher => AchievementDef {
+                name: "CATCHER",
+                description: "CAPTURE THE SAWBLADE 25 TIMES IN A SINGLE RUN",
+                goal: AchievementGoal::Captures(25),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AchievementDef {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub goal: AchievementGoal,
+}
+
+/// Condition checked against the current run.
+#[derive(Debug, Clone, Copy)]
+pub enum AchievementGoal {
+    Kills(usize),
+    KillsBy(KillSource, usize),
+    /// Kills in a row while the sawblade isn't reflected or captured by a paddle.
+    KillsWithoutBallReturning(usize),
+    Survive(f32),
+    MaxBallSpeed,
+    /// Survive until the time runs out without shooting.
+    WinWithoutFiring,
+    /// Time without the core getting hit, even blocked hits count.
+    Untouched(f32),
+    Captures(usize),
+}
+
+impl AchievementGoal {
+    fn reached(&self, stats: &RunStats, progress: &AchievementProgress) -> bool {
+        match *self {
+            AchievementGoal::Kills(count) => stats.total_kills() >= count,
+            AchievementGoal::KillsBy(source, count) => {
+                let kills = match source {
+                    KillSource::Gun => stats.gun_kills,
+                    KillSource::Ball => stats.ball_kills,
+                    KillSource::ReflectedProjectile => stats.reflected_kills,
+                };
+                kills >= count
+            }
+            AchievementGoal::KillsWithoutBallReturning(count) => progress.kill_streak >= count,
+            AchievementGoal::Survive(s) => stats.survival_s >= s,
+            AchievementGoal::MaxBallSpeed => progress.max_ball_speed,
+            AchievementGoal::WinWithoutFiring => progress.won && stats.shots_fired == 0,
+            AchievementGoal::Untouched(s) => progress.untouched_s >= s,
+            AchievementGoal::Captures(count) => stats.captures >= count,
+        }
+    }
+}
+
+/// Achievement has just been unlocked.
+#[derive(Event, Debug)]
+pub struct AchievementUnlocked(pub Achievement);
+
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Achievements {
+    unlocked: Vec<Achievement>,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    pub fn unlocked_count(&self) -> usize {
+        self.unlocked.len()
+    }
+}
+
+/// Progress of the current run that isn't part of [`RunStats`].
+#[derive(Resource, Debug, Default)]
+struct AchievementProgress {
+    kill_streak: usize,
+    untouched_s: f32,
+    max_ball_speed: bool,
+    won: bool,
+}
+
+fn reset_progress(mut progress: ResMut<AchievementProgress>) {
+    *progress = AchievementProgress::default();
+}
+
+fn track_progress(
+    mut progress: ResMut<AchievementProgress>,
+    mut killed_r: EventReader<EnemyKilled>,
+    mut reflected_r: EventReader<BallReflected>,
+    mut captured_r: EventReader<BallCaptured>,
+    mut dmg_r: EventReader<TakeDamage>,
+    ball_q: Query<&BallSpeed, With<Ball>>,
+    clock: Res<ModeClock>,
+    time: Res<Time>,
+) {
+    if reflected_r.read().count() > 0 || captured_r.read().count() > 0 {
+        progress.kill_streak = 0;
+    }
+    progress.kill_streak += killed_r.read().count();
+
+    progress.untouched_s += time.delta_secs();
+    if dmg_r.read().count() > 0 {
+        progress.untouched_s = 0.;
+    }
+
+    progress.max_ball_speed |= ball_q.iter().any(|speed| speed.0 >= BALL_MAX_SPEED);
+    progress.won |= clock.time_up;
+}
+
+fn unlock_achievements(
+    mut achievements: ResMut<Achievements>,
+    progress: Res<AchievementProgress>,
+    stats: Res<RunStats>,
+    mut unlocked_w: EventWriter<AchievementUnlocked>,
+) {
+    for achievement in Achievement::ALL {
+        if !achievements.is_unlocked(achievement)
+            && achievement.def().goal.reached(&stats, &progress)
+        {
+            achievements.unlocked.push(achievement);
+            unlocked_w.write(AchievementUnlocked(achievement));
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct ToastContainer;
+
+#[derive(Component, Debug, Default)]
+struct AchievementToast {
+    elapsed_s: f32,
+}
+
+/// Toasts outlive screen changes, so they are kept in their own container.
+fn spawn_toast_container(mut cmd: Commands) {
+    cmd.spawn((
+        Name::new("toast_container"),
+        ToastContainer,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.),
+            right: Val::Px(16.),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        Pickable::IGNORE,
+        GlobalZIndex(500),
+    ));
+}
+
+fn spawn_toasts(
+    mut unlocked_r: EventReader<AchievementUnlocked>,
+    container_q: Query<Entity, With<ToastContainer>>,
+    mut cmd: Commands,
+) -> Result {
+    for ev in unlocked_r.read() {
+        let def = ev.0.def();
+        cmd.entity(container_q.single()?).with_child((
+            Name::new("achievement_toast"),
+            AchievementToast::default(),
+            Node {
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.)),
+                ..default()
+            },
+            BackgroundColor(NODE_BG.with_alpha(0.)),
+            BorderRadius::all(Val::Px(8.)),
+            Pickable::IGNORE,
+            children![
+                (
+                    Text::new("ACHIEVEMENT UNLOCKED"),
+                    TextFont::from_font_size(16.),
+                    TextColor(LABEL_TEXT.with_alpha(0.)),
+                ),
+                (
+                    Text::new(def.name),
+                    TextFont::from_font_size(22.),
+                    TextColor(COL_BULLET.with_alpha(0.)),
+                ),
+            ],
+        ));
+    }
+    Ok(())
+}
+
+/// Uses real time so the toasts don't freeze during hitstop.
+fn fade_toasts(
+    mut toast_q: Query<(
+        Entity,
+        &mut AchievementToast,
+        &mut BackgroundColor,
+        &Children,
+    )>,
+    mut text_color_q: Query<&mut TextColor>,
+    time: Res<Time<Real>>,
+    mut cmd: Commands,
+) {
+    for (e, mut toast, mut bg, children) in &mut toast_q {
+        toast.elapsed_s += time.delta_secs();
+        if toast.elapsed_s >= TOAST_DURATION_S {
+            cmd.entity(e).despawn();
+            continue;
+        }
+
+        let alpha = (toast.elapsed_s / TOAST_FADE_S)
+            .min((TOAST_DURATION_S - toast.elapsed_s) / TOAST_FADE_S)
+            .clamp(0., 1.);
+        bg.0.set_alpha(alpha * 0.9);
+        for &child_e in children {
+            if let Ok(mut color) = text_color_q.get_mut(child_e) {
+                color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Arena layouts loaded from `assets/arenas/*.arena.ron`, picked per mode or rotated every wave.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use bevy_asset_loader::prelude::*;
+use serde::Deserialize;
+
+use crate::screen::{Screen, in_game_state};
+
+use super::{
+    mode::RunRules,
+    spawn::{ball::Ball, hazard::HazardDef, level::SpawnArena},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Arena>()
+        .init_asset_loader::<ArenaLoader>()
+        .init_resource::<ArenaWave>()
+        .add_systems(OnEnter(Screen::Game), reset_wave)
+        .add_systems(Update, rotate_arena.run_if(in_game_state));
+}
+
+/// Length of a wave, rotating arenas switch to the next layout after each one.
+const ARENA_WAVE_S: f32 = 45.;
+/// Segments of the polygon approximating a circular boundary.
+const CIRCLE_SEGMENTS: u32 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaKind {
+    Square,
+    Circle,
+    Hexagon,
+    Octagon,
+    Pillars,
+    Bumpers,
+}
+
+impl ArenaKind {
+    pub const ALL: [ArenaKind; 6] = [
+        ArenaKind::Square,
+        ArenaKind::Circle,
+        ArenaKind::Hexagon,
+        ArenaKind::Octagon,
+        ArenaKind::Pillars,
+        ArenaKind::Bumpers,
+    ];
+}
+
+/// Arena a mode is played in.
+#[derive(Debug, Clone, Copy)]
+pub enum ArenaSelection {
+    Fixed(ArenaKind),
+    /// Switches to the next arena every wave, looping around.
+    Rotating(&'static [ArenaKind]),
+}
+
+impl ArenaSelection {
+    pub fn kind(&self, wave: usize) -> ArenaKind {
+        match self {
+            ArenaSelection::Fixed(kind) => *kind,
+            ArenaSelection::Rotating(kinds) => kinds
+                .get(wave % kinds.len().max(1))
+                .copied()
+                .unwrap_or(ArenaKind::Square),
+        }
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct ArenaAssets {
+    #[asset(path = "arenas/square.arena.ron")]
+    pub square: Handle<Arena>,
+    #[asset(path = "arenas/circle.arena.ron")]
+    pub circle: Handle<Arena>,
+    #[asset(path = "arenas/hexagon.arena.ron")]
+    pub hexagon: Handle<Arena>,
+    #[asset(path = "arenas/octagon.arena.ron")]
+    pub octagon: Handle<Arena>,
+    #[asset(path = "arenas/pillars.arena.ron")]
+    pub pillars: Handle<Arena>,
+    #[asset(path = "arenas/bumpers.arena.ron")]
+    pub bumpers: Handle<Arena>,
+}
+
+impl ArenaAssets {
+    pub fn get(&self, kind: ArenaKind) -> &Handle<Arena> {
+        match kind {
+            ArenaKind::Square => &self.square,
+            ArenaKind::Circle => &self.circle,
+            ArenaKind::Hexagon => &self.hexagon,
+            ArenaKind::Octagon => &self.octagon,
+            ArenaKind::Pillars => &self.pillars,
+            ArenaKind::Bumpers => &self.bumpers,
+        }
+    }
+}
+
+/// Walls enclosing the playfield and the obstacles inside it.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Arena {
+    pub boundary: ArenaBoundary,
+    #[serde(default)]
+    pub obstacles: Vec<ArenaObstacle>,
+    /// The square arena lines up with the screen edges, so its walls aren't drawn.
+    #[serde(default)]
+    pub hidden_walls: bool,
+    /// Hazards spawned over time while the arena is up.
+    #[serde(default)]
+    pub script: Vec<ScriptEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptEntry {
+    /// Seconds since the arena was spawned.
+    #[serde(default)]
+    pub at_s: f32,
+    pub hazard: HazardDef,
+}
+
+impl Arena {
+    /// Wall segments as pairs of points.
+    pub fn segments(&self) -> Vec<(Vec2, Vec2)> {
+        let points = self.boundary.points();
+        (0..points.len())
+            .map(|i| (points[i], points[(i + 1) % points.len()]))
+            .collect()
+    }
+
+    /// Distance from the center to the closest wall.
+    pub fn inner_radius(&self) -> f32 {
+        self.segments()
+            .into_iter()
+            .map(|(a, b)| {
+                let ab = b - a;
+                let t = (-a).dot(ab) / ab.length_squared().max(f32::EPSILON);
+                (a + ab * t.clamp(0., 1.)).length()
+            })
+            .fold(f32::MAX, f32::min)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum ArenaBoundary {
+    /// Closed loop, each point is connected to the next one and the last one to the first.
+    Loop(Vec<(f32, f32)>),
+    /// Regular polygon centered on the origin, `radius` is the distance to its corners.
+    Polygon {
+        sides: u32,
+        radius: f32,
+        #[serde(default)]
+        rotation_deg: f32,
+    },
+    Circle(f32),
+}
+
+impl ArenaBoundary {
+    fn points(&self) -> Vec<Vec2> {
+        match self {
+            ArenaBoundary::Loop(points) => points.iter().map(|&p| Vec2::from(p)).collect(),
+            ArenaBoundary::Polygon {
+                sides,
+                radius,
+                rotation_deg,
+            } => polygon_points(*sides, *radius, *rotation_deg),
+            ArenaBoundary::Circle(radius) => polygon_points(CIRCLE_SEGMENTS, *radius, 0.),
+        }
+    }
+}
+
+fn polygon_points(sides: u32, radius: f32, rotation_deg: f32) -> Vec<Vec2> {
+    let sides = sides.max(3);
+    (0..sides)
+        .map(|i| {
+            let angle = rotation_deg.to_radians() + TAU * i as f32 / sides as f32;
+            Vec2::from_angle(angle) * radius
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArenaObstacle {
+    pub kind: ObstacleKind,
+    pub position: (f32, f32),
+    pub shape: ObstacleShape,
+    #[serde(default)]
+    pub rotation_deg: f32,
+}
+
+/// Obstacles are walls the ball bounces off, bumpers kick it away faster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ObstacleKind {
+    Pillar,
+    Bumper,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum ObstacleShape {
+    Circle(f32),
+    Rect(f32, f32),
+}
+
+#[derive(Default)]
+struct ArenaLoader;
+
+impl AssetLoader for ArenaLoader {
+    type Asset = Arena;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["arena.ron"]
+    }
+}
+
+/// Entity that's part of the current arena, despawned when the arena changes.
+#[derive(Component, Debug)]
+pub struct ArenaPart;
+
+#[derive(Resource, Debug, Default)]
+struct ArenaWave {
+    index: usize,
+    elapsed_s: f32,
+}
+
+fn reset_wave(mut wave: ResMut<ArenaWave>) {
+    *wave = ArenaWave::default();
+}
+
+fn rotate_arena(
+    mut wave: ResMut<ArenaWave>,
+    rules: Res<RunRules>,
+    part_q: Query<Entity, With<ArenaPart>>,
+    mut ball_q: Query<(&mut Transform, &Ball), Without<ChildOf>>,
+    arena_assets: Res<ArenaAssets>,
+    arenas: Res<Assets<Arena>>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let ArenaSelection::Rotating(_) = rules.arena else {
+        return;
+    };
+    wave.elapsed_s += time.delta_secs();
+    if wave.elapsed_s < ARENA_WAVE_S {
+        return;
+    }
+    wave.elapsed_s = 0.;
+    wave.index += 1;
+
+    let kind = rules.arena.kind(wave.index);
+    if kind == rules.arena.kind(wave.index - 1) {
+        return;
+    }
+    for e in &part_q {
+        cmd.entity(e).despawn();
+    }
+    cmd.trigger(SpawnArena(kind));
+
+    // keep the ball from ending up outside of a smaller arena
+    if let Some(arena) = arenas.get(arena_assets.get(kind)) {
+        let inner_radius = arena.inner_radius();
+        for (mut t, ball) in &mut ball_q {
+            let max_dist = inner_radius - ball.radius * 2.;
+            let pos = t.translation.truncate();
+            if pos.length() > max_dist {
+                t.translation = (pos.normalize_or_zero() * max_dist).extend(t.translation.z);
+            }
+        }
+    }
+}
@@ -4,13 +4,16 @@ use bevy_enoki::prelude::*;
 
 use crate::screen::Screen;
 
+use super::arena::ArenaAssets;
+
 pub(super) fn plugin(app: &mut App) {
     app.add_loading_state(
         LoadingState::new(Screen::Loading)
             .continue_to_state(Screen::Loaded)
             .load_collection::<SpriteAssets>()
             .load_collection::<SfxAssets>()
-            .load_collection::<MusicAssets>(),
+            .load_collection::<MusicAssets>()
+            .load_collection::<ArenaAssets>(),
     );
     app.add_systems(Startup, setup_particles);
 }
@@ -20,8 +23,9 @@ pub fn assets_exist(
     sfx: Option<Res<SfxAssets>>,
     music: Option<Res<MusicAssets>>,
     particles: Option<Res<ParticleAssets>>,
+    arenas: Option<Res<ArenaAssets>>,
 ) -> bool {
-    sprites.is_some() && sfx.is_some() && music.is_some() && particles.is_some()
+    sprites.is_some() && sfx.is_some() && music.is_some() && particles.is_some() && arenas.is_some()
 }
 
 #[derive(AssetCollection, Resource)]
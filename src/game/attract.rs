@@ -0,0 +1,122 @@
+//! Attract mode, a demo run played by the bot after idling on the title screen.
+
+use crate::{
+    screen::{NextTransitionedState, Screen, in_game_state},
+    theme::prelude::*,
+};
+use bevy::{
+    input::{gamepad::GamepadEvent, keyboard::KeyboardInput, mouse::MouseButtonInput},
+    prelude::*,
+};
+
+use super::{
+    bot::{BotPlayers, BotStrategy},
+    daily::DailyRun,
+    mode::ModeClock,
+    player::Multiplayer,
+    rng::RunSeed,
+    tutorial::TutorialRun,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<AttractRun>()
+        .add_systems(Update, start_demo_when_idle.run_if(in_state(Screen::Title)))
+        .add_systems(OnEnter(Screen::Game), spawn_overlay.run_if(attract_run))
+        .add_systems(Update, end_demo.run_if(in_game_state.and(attract_run)))
+        .add_systems(OnEnter(Screen::Title), clear_demo.run_if(attract_run));
+}
+
+/// Idle time on the title screen before the demo starts.
+const IDLE_BEFORE_DEMO_S: f32 = 20.;
+const DEMO_DURATION_S: f32 = 60.;
+
+/// The current run is a demo played by the bot.
+#[derive(Resource, Debug, Default)]
+pub struct AttractRun(pub bool);
+
+pub fn attract_run(attract: Res<AttractRun>) -> bool {
+    attract.0
+}
+
+fn start_demo_when_idle(
+    mut idle_s: Local<f32>,
+    mut kb_evr: EventReader<KeyboardInput>,
+    mut mouse_btn_evr: EventReader<MouseButtonInput>,
+    mut cursor_evr: EventReader<CursorMoved>,
+    mut gamepad_evr: EventReader<GamepadEvent>,
+    mut attract: ResMut<AttractRun>,
+    mut bots: ResMut<BotPlayers>,
+    mut multiplayer: ResMut<Multiplayer>,
+    mut daily: ResMut<DailyRun>,
+    mut tutorial: ResMut<TutorialRun>,
+    mut seed: ResMut<RunSeed>,
+    mut next_screen: ResMut<NextTransitionedState>,
+    time: Res<Time>,
+) {
+    let active = kb_evr.read().count() > 0
+        || mouse_btn_evr.read().count() > 0
+        || cursor_evr.read().count() > 0
+        || gamepad_evr.read().count() > 0;
+    if active {
+        *idle_s = 0.;
+        return;
+    }
+
+    *idle_s += time.delta_secs();
+    if *idle_s < IDLE_BEFORE_DEMO_S {
+        return;
+    }
+
+    *idle_s = 0.;
+    attract.0 = true;
+    bots.0 = Some(BotStrategy::Balanced);
+    *multiplayer = Multiplayer::Off;
+    daily.0 = None;
+    tutorial.0 = false;
+    seed.0 = None;
+    next_screen.set(Screen::Game);
+}
+
+fn spawn_overlay(mut cmd: Commands) {
+    cmd.spawn((
+        Name::new("attract_overlay"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.),
+            width: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Game),
+        children![
+            widget::header("PORCLE"),
+            widget::label("DEMO - PRESS ANY BUTTON"),
+        ],
+    ));
+}
+
+/// Any button press or the end of the demo time returns to the title screen.
+fn end_demo(
+    mut kb_evr: EventReader<KeyboardInput>,
+    mut mouse_btn_evr: EventReader<MouseButtonInput>,
+    mut gamepad_evr: EventReader<GamepadEvent>,
+    clock: Res<ModeClock>,
+    mut next_screen: ResMut<NextTransitionedState>,
+) {
+    let pressed = kb_evr.read().count() > 0
+        || mouse_btn_evr.read().count() > 0
+        || gamepad_evr
+            .read()
+            .any(|ev| matches!(ev, GamepadEvent::Button(_)));
+    if pressed || clock.elapsed_s >= DEMO_DURATION_S {
+        next_screen.set(Screen::Title);
+    }
+}
+
+/// Kept until the game screen is exited, so the end of the demo never counts towards progress.
+fn clear_demo(mut attract: ResMut<AttractRun>, mut bots: ResMut<BotPlayers>) {
+    attract.0 = false;
+    bots.0 = None;
+}
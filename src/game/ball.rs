@@ -1,19 +1,18 @@
 use std::cmp::Ordering;
 
 use avian2d::prelude::*;
-use bevy::{core_pipeline::bloom::Bloom, prelude::*};
-use bevy_enoki::{
-    ParticleEffectHandle,
-    prelude::{OneShot, ParticleSpawnerState},
-};
+use bevy::{core_pipeline::bloom::Bloom, ecs::system::SystemParam, prelude::*};
+use bevy_enoki::{ParticleEffectHandle, prelude::ParticleSpawnerState};
 use bevy_trauma_shake::{ShakeSettings, Shakes};
+use bevy_tweening::Animator;
 
 use crate::{
     BLOOM_BASE, GAME_SIZE,
     ext::Vec2Ext,
     game::{
-        movement::MovementPaused, spawn::paddle::PADDLE_COLL_HEIGHT,
-        tween::get_relative_sprite_color_anim,
+        movement::MovementPaused,
+        spawn::paddle::PADDLE_COLL_HEIGHT,
+        tween::{get_relative_scale_tween, get_relative_sprite_color_anim},
     },
     math::asymptotic_smoothing_with_delta_time,
     screen::in_game_state,
@@ -22,41 +21,71 @@ use crate::{
 
 use super::{
     assets::ParticleAssets,
+    collision::{BallCollision, CollisionSet, GameLayer},
+    core::{DamageSource, TakeDamage},
     gun::ProjectileDespawn,
-    movement::{Homing, MoveDirection, Speed, Velocity, speed_factor},
+    hazard::{BarrierHit, BonusLaneEntered, DropTargetHit, PortalEntered},
+    mode::RunRules,
+    movement::{Homing, MoveDirection, Speed, speed_factor},
     paddle::PaddleKnockback,
+    parry::BloomFlash,
+    pool::{PoolCommandsExt, PoolKey},
+    rng::DropRng,
     score::Score,
     spawn::{
         ball::{Ball, InsidePaddleRadius},
-        enemy::Enemy,
-        level::Wall,
+        enemy::{Enemy, EnemyKilled, KillSource},
+        hazard::{Barrier, BonusLane, DropTarget, Portal, SpeedStrip},
+        level::{Bumper, Core, Wall},
         paddle::{PADDLE_RADIUS, Paddle, PaddleAmmo, PaddleMode},
+        pickup::roll_pickup_drop,
         projectile::Projectile,
     },
-    time::Cooldown,
+    time::{Cooldown, DilateTime},
     tween::lerp_color,
+    versus::{Garbage, SendGarbage},
 };
 
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
-    app.init_resource::<MaxBallSpeedFactor>().add_systems(
-        Update,
-        (
-            balls_inside_core,
-            update_ball_speed,
-            handle_ball_collisions,
-            color_ball,
-            rotate_ball,
-            rotate_ball_particles,
-            boost_postprocessing_based_on_ball_speed,
-            update_ball_speed_factor,
-            update_trauma_based_on_ball_speed,
-        )
-            .run_if(in_game_state),
-    );
+    app.init_resource::<MaxBallSpeedFactor>()
+        .add_event::<BallReflected>()
+        .add_event::<BallCaptured>()
+        .add_systems(
+            Update,
+            (
+                balls_inside_core,
+                update_ball_speed,
+                (handle_ball_collisions, shapecast_nearest_enemy)
+                    .chain()
+                    .after(CollisionSet),
+                color_ball,
+                rotate_ball,
+                rotate_ball_particles,
+                boost_postprocessing_based_on_ball_speed,
+                update_ball_speed_factor,
+                update_trauma_based_on_ball_speed,
+            )
+                .run_if(in_game_state),
+        );
 }
 
 pub const BALL_BASE_SPEED: f32 = 250.;
+pub const BALL_MAX_SPEED: f32 = BALL_BASE_SPEED * 5.;
+/// Speed multiplier of bumper hits, walls slow the ball down instead.
+const BUMPER_KICK: f32 = 1.3;
+
+/// The ball has been reflected by a paddle.
+#[derive(Event, Debug)]
+pub struct BallReflected {
+    pub paddle_e: Entity,
+}
+
+/// The ball has been caught by a paddle in capture mode.
+#[derive(Event, Debug)]
+pub struct BallCaptured {
+    pub paddle_e: Entity,
+}
 
 #[derive(Component, Debug, Deref, DerefMut, Reflect)]
 pub struct BallSpeed(pub f32);
@@ -79,12 +108,43 @@ impl MaxBallSpeedFactor {
 #[derive(Component, Debug)]
 struct ShapecastNearestEnemy;
 
+/// Walls and hazards the ball can hit.
+#[derive(SystemParam)]
+struct ArenaQueries<'w, 's> {
+    wall_q: Query<'w, 's, (), With<Wall>>,
+    barrier_q: Query<'w, 's, (), With<Barrier>>,
+    portal_q: Query<'w, 's, (), With<Portal>>,
+    speed_strip_q: Query<'w, 's, &'static SpeedStrip>,
+    bumper_q: Query<'w, 's, (), With<Bumper>>,
+    drop_target_q: Query<'w, 's, (), With<DropTarget>>,
+    bonus_lane_q: Query<'w, 's, (), With<BonusLane>>,
+}
+
+/// Events written by [`handle_ball_collisions`], grouped to stay under the system parameter limit.
+#[derive(SystemParam)]
+struct BallCollisionEvents<'w> {
+    knockback_paddle: EventWriter<'w, PaddleKnockback>,
+    ball_reflected: EventWriter<'w, BallReflected>,
+    ball_captured: EventWriter<'w, BallCaptured>,
+    enemy_killed: EventWriter<'w, EnemyKilled>,
+    taken_dmg: EventWriter<'w, TakeDamage>,
+    projectile_hit: EventWriter<'w, ProjectileDespawn>,
+    dilate_time: EventWriter<'w, DilateTime>,
+    portal_entered: EventWriter<'w, PortalEntered>,
+    barrier_hit: EventWriter<'w, BarrierHit>,
+    drop_target_hit: EventWriter<'w, DropTargetHit>,
+    bonus_lane_entered: EventWriter<'w, BonusLaneEntered>,
+}
+
 fn balls_inside_core(
     mut cmd: Commands,
     ball_q: Query<(Entity, &GlobalTransform, Option<&InsidePaddleRadius>), With<Ball>>,
+    core_q: Query<&GlobalTransform, With<Core>>,
 ) {
     for (e, t, inside) in &ball_q {
-        let inside_core = t.translation().length() < PADDLE_RADIUS * 1.1;
+        let inside_core = core_q
+            .iter()
+            .any(|core_t| t.translation().distance(core_t.translation()) < PADDLE_RADIUS * 1.1);
         if inside_core && inside.is_none() {
             cmd.entity(e).insert(InsidePaddleRadius);
             cmd.entity(e).remove::<Homing>();
@@ -104,6 +164,7 @@ fn balls_inside_core(
 fn update_ball_speed(
     mut ball_q: Query<(&GlobalTransform, &mut Speed, &mut BallSpeed), With<Ball>>,
     paddle_mode_q: Query<&PaddleMode>,
+    rules: Res<RunRules>,
     time: Res<Time>,
 ) {
     let ball_captured = paddle_mode_q
@@ -121,28 +182,19 @@ fn update_ball_speed(
         // let dist = t.translation().length();
         // let factor = ((dist - PADDLE_RADIUS) / 120.0).clamp(0., 1.).powf(2.0);
         // speed.0 = ball_speed.0 * (1. + factor * 0.5);
-        speed.0 = ball_speed.0;
+        speed.0 = ball_speed.0 * rules.ball_speed_mult;
     }
 }
 
 fn handle_ball_collisions(
-    phys_spatial: SpatialQuery,
+    mut collision_r: EventReader<BallCollision>,
     mut ball_q: Query<(
-        Entity,
-        &GlobalTransform,
         &mut Ball,
-        &Velocity,
         &mut MoveDirection,
         &Speed,
         &mut BallSpeed,
+        Has<Cooldown<Portal>>,
     )>,
-    ball_shapecast_q: Query<
-        (),
-        (
-            With<ShapecastNearestEnemy>,
-            Without<Cooldown<MovementPaused>>,
-        ),
-    >,
     mut paddle_q: Query<(
         Entity,
         &mut PaddleAmmo,
@@ -150,212 +202,282 @@ fn handle_ball_collisions(
         &Paddle,
         &mut PaddleMode,
     )>,
-    enemy_q: Query<&GlobalTransform, With<Enemy>>,
+    enemy_q: Query<(&Enemy, Has<Garbage>)>,
+    core_q: Query<(), With<Core>>,
     projectile_q: Query<(), With<Projectile>>,
-    wall_q: Query<(), With<Wall>>,
+    arena: ArenaQueries,
     mut cmd: Commands,
-    time: Res<Time>,
     mut shake: Shakes,
     particles: Res<ParticleAssets>,
     ball_speed_factor: Res<MaxBallSpeedFactor>,
     mut score: ResMut<Score>,
-    mut knockback_paddle_ev_w: EventWriter<PaddleKnockback>,
-    mut projectile_hit_w: EventWriter<ProjectileDespawn>,
+    mut drop_rng: ResMut<DropRng>,
+    mut events: BallCollisionEvents,
 ) {
-    for (ball_e, ball_t, mut ball, vel, mut direction, speed, mut ball_speed) in &mut ball_q {
-        if (vel.velocity() - Vec2::ZERO).length() < f32::EPSILON {
-            // stationary ball
+    for hit in collision_r.read() {
+        let ball_e = hit.ball_e;
+        let Ok((mut ball, mut direction, speed, mut ball_speed, teleported)) =
+            ball_q.get_mut(ball_e)
+        else {
             continue;
-        }
-        for hit in phys_spatial.shape_hits(
-            &Collider::circle(ball.radius),
-            ball_t.translation().truncate(),
-            0.,
-            Dir2::new(vel.velocity()).expect("Non zero velocity"),
-            100,
-            &ShapeCastConfig::from_max_distance((speed.0 * 1.05) * time.delta_secs()),
-            &SpatialQueryFilter::default(),
-        ) {
-            let hit_e = hit.entity;
-            if let Ok((paddle_e, mut ammo, paddle_t, paddle, mut paddle_mode)) =
-                paddle_q.get_mut(hit_e)
-            {
-                if let PaddleMode::Captured { .. } = *paddle_mode {
-                    continue;
-                }
-
-                if time.elapsed_secs() < ball.last_reflection_time + 0.2 {
-                    // ignore consecutive hits
-                    continue;
-                }
+        };
+        let hit_e = hit.other_e;
+        if let Ok((paddle_e, mut ammo, paddle_t, paddle, mut paddle_mode)) = paddle_q.get_mut(hit_e)
+        {
+            if let PaddleMode::Captured { .. } = *paddle_mode {
+                continue;
+            }
 
-                let hit_point_local = paddle_t
-                    .affine()
-                    .inverse()
-                    .transform_point(hit.point1.extend(0.));
-                // limit upper treshold to 1 to account for the collider rounding
-                let ratio = hit_point_local.y / (PADDLE_COLL_HEIGHT / 2.);
-                let angle_factor = ratio
-                    .abs()
-                    .min(1.0)
-                    // exp decay
-                    .powf(1.5);
-                // aim the ball based on where it landed on the paddle
-                // the further it lands from the center, the greater the reflection angle
-                // if x is positive, then the hit is from outside => reflect it back outside
-                let hit_from_outside = hit_point_local.x > 0.;
-                let origit_rot = if hit_from_outside { 180. } else { 0. };
-                let max_reflection_angle = 20.0;
-                let angle = angle_factor
-                    * ratio.signum()
-                    * max_reflection_angle
-                    * hit_point_local.x.signum()
+            let hit_point_local = paddle_t
+                .affine()
+                .inverse()
+                .transform_point(hit.point.extend(0.));
+            // limit upper treshold to 1 to account for the collider rounding
+            let ratio = hit_point_local.y / (PADDLE_COLL_HEIGHT / 2.);
+            let angle_factor = ratio
+                .abs()
+                .min(1.0)
+                // exp decay
+                .powf(1.5);
+            // aim the ball based on where it landed on the paddle
+            // the further it lands from the center, the greater the reflection angle
+            // if x is positive, then the hit is from outside => reflect it back outside
+            let hit_from_outside = hit_point_local.x > 0.;
+            let origit_rot = if hit_from_outside { 180. } else { 0. };
+            let max_reflection_angle = 20.0;
+            let angle =
+                angle_factor * ratio.signum() * max_reflection_angle * hit_point_local.x.signum()
                     + origit_rot;
-                debug!(angle_factor, angle, "paddle hit");
-
-                // allow capturing only from the inside of the core
-                if matches!(*paddle_mode, PaddleMode::Capture) && hit_point_local.x < 0. {
-                    // catching ball
-                    *paddle_mode = PaddleMode::Captured {
-                        shoot_rotation: Rot2::radians(angle.to_radians()),
-                        ball_e,
-                    };
-                    cmd.entity(ball_e)
-                        .set_parent_in_place(paddle_e)
-                        .insert(MovementPaused);
-                    cmd.entity(paddle.reflect_e)
-                        .try_insert(get_relative_sprite_color_anim(
-                            paddle_mode.color(),
-                            150,
-                            Some(EaseFunction::QuadraticOut),
-                        ));
-                } else {
-                    // reflecting ball
-                    shake.add_trauma(
-                        0.15 + 0.15 * speed.speed_factor(BALL_BASE_SPEED, BALL_BASE_SPEED * 2.0),
-                    );
-                    cmd.spawn((
+            debug!(angle_factor, angle, "paddle hit");
+
+            // allow capturing only from the inside of the core
+            if matches!(*paddle_mode, PaddleMode::Capture) && hit_point_local.x < 0. {
+                // catching ball
+                *paddle_mode = PaddleMode::Captured {
+                    shoot_rotation: Rot2::radians(angle.to_radians()),
+                    ball_e,
+                };
+                cmd.entity(ball_e)
+                    .set_parent_in_place(paddle_e)
+                    .insert(MovementPaused);
+                events.ball_captured.write(BallCaptured { paddle_e });
+                cmd.entity(paddle.reflect_e)
+                    .try_insert(get_relative_sprite_color_anim(
+                        paddle_mode.color(),
+                        150,
+                        Some(EaseFunction::QuadraticOut),
+                    ));
+            } else {
+                // reflecting ball
+                shake.add_trauma(
+                    0.15 + 0.15 * speed.speed_factor(BALL_BASE_SPEED, BALL_BASE_SPEED * 2.0),
+                );
+                cmd.spawn_particles(
+                    PoolKey::CircleParticles,
+                    (
                         particles.circle_particle_spawner(),
                         ParticleEffectHandle(particles.reflection.clone_weak()),
-                        Transform::from_translation(hit.point1.extend(10.))
+                        Transform::from_translation(hit.point.extend(10.))
                             .with_rotation(paddle_t.up().truncate().to_quat()),
-                        OneShot::Despawn,
-                    ));
-                    // clamp to min speed in case the ball has come back to core
-                    ball_speed.0 = (speed.0 * 1.225).clamp(BALL_BASE_SPEED, BALL_BASE_SPEED * 5.0);
-                    let rot = Quat::from_rotation_z(angle.to_radians());
-                    let new_dir = (rot * -paddle_t.right()).truncate().normalize_or_zero();
-                    direction.0 = new_dir;
-
-                    // ammo
-                    ammo.offset(ball_speed_factor.ammo_bonus() as isize);
-                    let cooldown =
-                        0.1 + speed.speed_factor(BALL_BASE_SPEED, BALL_BASE_SPEED * 1.5) * 0.2;
-                    cmd.entity(ball_e)
-                        .insert(MovementPaused::cooldown(cooldown));
-                    ball.last_reflection_time = time.elapsed_secs() + cooldown;
-
-                    knockback_paddle_ev_w.write(PaddleKnockback(if hit_from_outside {
-                        -15.
-                    } else {
-                        15.
-                    }));
-                }
-            } else if wall_q.contains(hit_e) {
-                if time.elapsed_secs() < ball.last_reflection_time + 0.1 {
-                    // ignore consecutive hits
-                    continue;
-                }
-
-                let speed_factor = speed.speed_factor(BALL_BASE_SPEED * 0.5, BALL_BASE_SPEED * 2.0);
-
-                // shake
-                shake.add_trauma(0.2 + 0.125 * speed_factor);
-
-                // freeze movement
-                let cooldown = 0.085 + speed_factor * 0.125;
-                cmd.entity(ball_e)
-                    .insert((MovementPaused::cooldown(cooldown), ShapecastNearestEnemy));
-                ball.last_reflection_time = time.elapsed_secs() + cooldown;
-
-                // todo: need to fix
-                // // particles
-                // cmd.spawn((
-                //     particles.particle_spawner(
-                //         particles.reflection.clone(),
-                //         Transform::from_translation(hit.point1.extend(10.)).with_rotation(
-                //             Quat::from_rotation_z(-90f32.to_radians()) * hit.normal1.to_quat(),
-                //         ),
-                //     ),
-                //     OneShot::Despawn,
-                // ));
-
+                    ),
+                );
+                // clamp to min speed in case the ball has come back to core
+                ball_speed.0 = (speed.0 * 1.225).clamp(BALL_BASE_SPEED, BALL_MAX_SPEED);
+                let rot = Quat::from_rotation_z(angle.to_radians());
+                let new_dir = (rot * -paddle_t.right()).truncate().normalize_or_zero();
+                direction.0 = new_dir;
+
+                // ammo
+                ammo.offset(ball_speed_factor.ammo_bonus() as isize);
+                let hitstop =
+                    0.1 + speed.speed_factor(BALL_BASE_SPEED, BALL_BASE_SPEED * 1.5) * 0.2;
+                events
+                    .dilate_time
+                    .write(DilateTime::freeze((hitstop * 1000.) as u64));
+                ball.launched_from = None;
+
+                events.knockback_paddle.write(PaddleKnockback {
+                    paddle_e,
+                    offset: if hit_from_outside { -15. } else { 15. },
+                });
+                events.ball_reflected.write(BallReflected { paddle_e });
+            }
+        } else if arena.wall_q.contains(hit_e) {
+            if arena.barrier_q.contains(hit_e) {
+                events.barrier_hit.write(BarrierHit(hit_e));
+            }
+            if arena.drop_target_q.contains(hit_e) {
+                events.drop_target_hit.write(DropTargetHit(hit_e));
+            }
+            let bumper = arena.bumper_q.contains(hit_e);
+
+            let speed_factor = speed.speed_factor(BALL_BASE_SPEED * 0.5, BALL_BASE_SPEED * 2.0);
+
+            // shake
+            shake.add_trauma(0.2 + 0.125 * speed_factor + if bumper { 0.15 } else { 0. });
+
+            // freeze
+            let hitstop = 0.085 + speed_factor * 0.125;
+            events
+                .dilate_time
+                .write(DilateTime::freeze((hitstop * 1000.) as u64));
+            cmd.entity(ball_e).insert(ShapecastNearestEnemy);
+
+            // todo: need to fix
+            // // particles
+            // cmd.spawn((
+            //     particles.particle_spawner(
+            //         particles.reflection.clone(),
+            //         Transform::from_translation(hit.point.extend(10.)).with_rotation(
+            //             Quat::from_rotation_z(-90f32.to_radians()) * hit.normal.to_quat(),
+            //         ),
+            //     ),
+            //     OneShot::Despawn,
+            // ));
+
+            if bumper {
+                ball_speed.0 = (ball_speed.0 * BUMPER_KICK).min(BALL_MAX_SPEED);
+                cmd.entity(hit_e).insert(Animator::new(
+                    get_relative_scale_tween(
+                        Vec3::splat(1.25),
+                        60,
+                        Some(EaseFunction::QuadraticOut),
+                    )
+                    .then(get_relative_scale_tween(
+                        Vec3::ONE,
+                        220,
+                        Some(EaseFunction::BackOut),
+                    )),
+                ));
+            } else {
                 ball_speed.0 *= 0.9;
-                let dir = vel.velocity().normalize_or_zero();
-                let reflect = dir - (2.0 * dir.dot(hit.normal1) * hit.normal1);
-                direction.0 = reflect;
-            } else if enemy_q.contains(hit_e) {
-                if let Some((_, _, _, _, paddle_mode, ..)) = paddle_q.iter().next() {
-                    if matches!(paddle_mode, PaddleMode::Captured { .. }) {
-                        continue;
-                    }
-                }
+            }
+            direction.0 = reflect(direction.0, hit.normal);
+        } else if arena.portal_q.contains(hit_e) {
+            if !teleported {
+                events.portal_entered.write(PortalEntered {
+                    ball_e,
+                    portal_e: hit_e,
+                });
+            }
+        } else if arena.bonus_lane_q.contains(hit_e) {
+            events.bonus_lane_entered.write(BonusLaneEntered(hit_e));
+        } else if let Ok(strip) = arena.speed_strip_q.get(hit_e) {
+            ball_speed.0 = (ball_speed.0 * strip.speed_mult).min(BALL_MAX_SPEED);
+            shake.add_trauma(0.1);
+        } else if ball
+            .launched_from
+            .is_some_and(|core_e| core_e != hit_e && core_q.contains(hit_e))
+        {
+            // launched at the opponent's core
+            events
+                .taken_dmg
+                .write(TakeDamage(hit_e, DamageSource::Ball));
+            ball.launched_from = None;
+            shake.add_trauma(0.3);
+
+            direction.0 = reflect(direction.0, hit.normal);
+        } else if let Ok((enemy, garbage)) = enemy_q.get(hit_e) {
+            let captured = paddle_q.iter().any(|(.., paddle_mode)| match paddle_mode {
+                PaddleMode::Captured {
+                    ball_e: captured_e, ..
+                } => *captured_e == ball_e,
+                _ => false,
+            });
+            if captured {
+                continue;
+            }
 
-                cmd.entity(hit_e).despawn();
-                shake.add_trauma(0.15);
-                // particles
-                cmd.spawn((
+            if !garbage {
+                cmd.trigger(SendGarbage {
+                    from_core_e: enemy.core_e,
+                });
+            }
+            cmd.release(hit_e);
+            events.enemy_killed.write(EnemyKilled {
+                kind: enemy.kind,
+                by: KillSource::Ball,
+            });
+            roll_pickup_drop(&mut cmd, &mut drop_rng.0, hit.point);
+            shake.add_trauma(0.15);
+            // particles
+            cmd.spawn_particles(
+                PoolKey::SquareParticles,
+                (
                     particles.square_particle_spawner(),
                     ParticleEffectHandle(particles.enemy.clone_weak()),
-                    Transform::from_translation(hit.point1.extend(10.)),
-                    OneShot::Despawn,
-                ));
-                // freeze
-                let speed_factor =
-                    speed.speed_factor(BALL_BASE_SPEED * 0.5, BALL_BASE_SPEED * 1.75);
-                let cooldown = 0.08 + speed_factor * 0.06;
-                cmd.entity(ball_e)
-                    .insert((MovementPaused::cooldown(cooldown), ShapecastNearestEnemy));
-                score.0 += 1;
-            } else if projectile_q.contains(hit_e) {
-                projectile_hit_w.write(ProjectileDespawn(hit_e));
-            }
+                    Transform::from_translation(hit.point.extend(10.)),
+                ),
+            );
+            // freeze
+            let speed_factor = speed.speed_factor(BALL_BASE_SPEED * 0.5, BALL_BASE_SPEED * 1.75);
+            let hitstop = 0.08 + speed_factor * 0.06;
+            events
+                .dilate_time
+                .write(DilateTime::freeze((hitstop * 1000.) as u64));
+            cmd.entity(ball_e).insert(ShapecastNearestEnemy);
+            score.0 += 1;
+        } else if projectile_q.contains(hit_e) {
+            events.projectile_hit.write(ProjectileDespawn(hit_e));
         }
+    }
+}
+
+/// Mirrors the direction off a surface, unless it's already heading away after an earlier
+/// contact, e.g. hitting two wall segments in a corner at once.
+fn reflect(dir: Vec2, normal: Vec2) -> Vec2 {
+    let dir = dir.normalize_or_zero();
+    if dir.dot(normal) >= 0. {
+        return dir;
+    }
+    dir - (2.0 * dir.dot(normal) * normal)
+}
 
-        if ball_shapecast_q.get(ball_e).is_ok() {
-            cmd.entity(ball_e).remove::<ShapecastNearestEnemy>();
-            debug!("shapecasting nearest enemy");
-            let radius = 170.;
-            let origin = ball_t.translation().truncate() + direction.0 * 150.;
-            for hit in phys_spatial
-                .shape_hits(
-                    &Collider::circle(radius),
-                    origin,
-                    0.,
-                    Dir2::new(direction.0).expect("Non zero velocity"),
-                    100,
-                    &ShapeCastConfig {
-                        max_distance: (speed.0 * 1.05) * time.delta_secs(),
-                        ignore_origin_penetration: true,
-                        ..default()
-                    },
-                    &SpatialQueryFilter::default(),
-                )
-                .iter()
-            {
-                if let Ok(enemy_t) = enemy_q.get(hit.entity) {
-                    let enemy_pos = enemy_t.translation();
-                    if enemy_pos.abs().max_element() > (GAME_SIZE / 2. - 50.) {
-                        // outside window
-                        continue;
-                    }
-
-                    debug!(pos = ?enemy_t.translation(), "nearest enemy");
-                    direction.0 = (enemy_t.translation() - ball_t.translation())
-                        .truncate()
-                        .normalize_or_zero();
-                    break;
+/// Aims the ball at the closest enemy ahead after it bounces off a wall or kills an enemy.
+fn shapecast_nearest_enemy(
+    phys_spatial: SpatialQuery,
+    mut ball_q: Query<
+        (Entity, &GlobalTransform, &mut MoveDirection, &Speed),
+        (With<Ball>, With<ShapecastNearestEnemy>),
+    >,
+    enemy_q: Query<&GlobalTransform, With<Enemy>>,
+    // a hitstop or slow motion would shrink the cast to nothing with the virtual time
+    time: Res<Time<Real>>,
+    mut cmd: Commands,
+) {
+    for (ball_e, ball_t, mut direction, speed) in &mut ball_q {
+        cmd.entity(ball_e).remove::<ShapecastNearestEnemy>();
+        debug!("shapecasting nearest enemy");
+        let radius = 170.;
+        let origin = ball_t.translation().truncate() + direction.0 * 150.;
+        for hit in phys_spatial
+            .shape_hits(
+                &Collider::circle(radius),
+                origin,
+                0.,
+                Dir2::new(direction.0).expect("Non zero velocity"),
+                100,
+                &ShapeCastConfig {
+                    max_distance: (speed.0 * 1.05) * time.delta_secs(),
+                    ignore_origin_penetration: true,
+                    ..default()
+                },
+                &SpatialQueryFilter::from_mask(GameLayer::Enemy),
+            )
+            .iter()
+        {
+            if let Ok(enemy_t) = enemy_q.get(hit.entity) {
+                let enemy_pos = enemy_t.translation();
+                if enemy_pos.abs().max_element() > (GAME_SIZE / 2. - 50.) {
+                    // outside window
+                    continue;
                 }
+
+                debug!(pos = ?enemy_t.translation(), "nearest enemy");
+                direction.0 = (enemy_t.translation() - ball_t.translation())
+                    .truncate()
+                    .normalize_or_zero();
+                break;
             }
         }
     }
@@ -392,10 +514,11 @@ fn update_ball_speed_factor(
 
 fn boost_postprocessing_based_on_ball_speed(
     factor: Res<MaxBallSpeedFactor>,
+    bloom_flash: Res<BloomFlash>,
     mut bloom_q: Query<&mut Bloom>,
 ) {
     for mut bloom in &mut bloom_q {
-        bloom.intensity = BLOOM_BASE + 0.175 * factor.0;
+        bloom.intensity = BLOOM_BASE + 0.175 * factor.0 + bloom_flash.0;
     }
 }
 
@@ -426,13 +549,7 @@ fn rotate_ball(
 
 fn rotate_ball_particles(
     ball_q: Query<(Entity, &Ball, &MoveDirection)>,
-    ball_paused_q: Query<
-        (),
-        (
-            With<Ball>,
-            Or<(With<MovementPaused>, With<Cooldown<MovementPaused>>)>,
-        ),
-    >,
+    ball_paused_q: Query<(), (With<Ball>, With<MovementPaused>)>,
     mut particles_q: Query<(&mut Transform, &mut ParticleSpawnerState)>,
     factor: Res<MaxBallSpeedFactor>,
 ) {
@@ -0,0 +1,231 @@
+//! Simple bot playing through the same [`PlayerInput`] a human fills.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::screen::in_game_state;
+
+use super::{
+    ball::BallReflected,
+    input::{PlayerAction, PlayerInput},
+    movement::{MoveDirection, MovementPaused},
+    spawn::{
+        ball::Ball,
+        enemy::Enemy,
+        paddle::{PADDLE_RADIUS, Paddle, PaddleAmmo, PaddleMode},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BotPlayers>()
+        .add_observer(hand_paddle_to_bot)
+        .add_systems(
+            PreUpdate,
+            drive_bots
+                .in_set(InputManagerSystem::ManualControl)
+                .run_if(in_game_state),
+        );
+}
+
+/// Paddle is far enough from the target to rotate towards it at full speed.
+const SPIN_LEAD_ANGLE: f32 = PI / 2.;
+/// Max angle between the paddle and an enemy to shoot at it.
+const SHOOT_ANGLE: f32 = 0.15;
+/// Turns are a bit longer than the gestures so they register even if the start is sloppy.
+const AMMO_SPIN_ANGLE: f32 = TAU * 1.15;
+const RECALL_SPIN_ANGLE: f32 = -TAU * 2.15;
+/// The ball is recalled when it hasn't been reflected for this long.
+const RECALL_AFTER_S: f32 = 12.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BotStrategy {
+    /// Shoots, tracks the ball and performs cycles.
+    #[default]
+    Balanced,
+    /// Relies on the gun and ignores the ball unless it has been captured.
+    GunOnly,
+    /// Never shoots, only plays the ball.
+    BallOnly,
+}
+
+impl BotStrategy {
+    pub const ALL: [BotStrategy; 3] = [
+        BotStrategy::Balanced,
+        BotStrategy::GunOnly,
+        BotStrategy::BallOnly,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BotStrategy::Balanced => "balanced",
+            BotStrategy::GunOnly => "gun_only",
+            BotStrategy::BallOnly => "ball_only",
+        }
+    }
+
+    fn def(&self) -> BotStrategyDef {
+        match self {
+            BotStrategy::Balanced => BotStrategyDef::default(),
+            BotStrategy::GunOnly => BotStrategyDef {
+                track_ball: false,
+                ..default()
+            },
+            BotStrategy::BallOnly => BotStrategyDef {
+                shoot: false,
+                ammo_cycles: false,
+                ..default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BotStrategyDef {
+    shoot: bool,
+    /// Move in front of the ball heading to the rail.
+    track_ball: bool,
+    /// Gain ammo with the CCW cycle once it runs out.
+    ammo_cycles: bool,
+}
+
+impl Default for BotStrategyDef {
+    fn default() -> Self {
+        Self {
+            shoot: true,
+            track_ball: true,
+            ammo_cycles: true,
+        }
+    }
+}
+
+/// Paddles spawned while this is set are handed over to the bot.
+#[derive(Resource, Debug, Default)]
+pub struct BotPlayers(pub Option<BotStrategy>);
+
+/// Paddle driven by the bot instead of a human, it should have an empty input map.
+#[derive(Component, Debug, Default)]
+pub struct Bot {
+    strategy: BotStrategyDef,
+    /// Remaining angle of the cycle being performed, negative for CW.
+    spin: f32,
+    prev_angle: Option<f32>,
+    since_reflect_s: f32,
+}
+
+/// Bot paddles ignore human input, the bot fills their action state instead.
+fn hand_paddle_to_bot(trigger: Trigger<OnAdd, Paddle>, bots: Res<BotPlayers>, mut cmd: Commands) {
+    if let Some(strategy) = bots.0 {
+        cmd.entity(trigger.target()).insert((
+            Bot {
+                strategy: strategy.def(),
+                ..default()
+            },
+            InputMap::<PlayerAction>::default(),
+        ));
+    }
+}
+
+fn drive_bots(
+    mut bot_q: Query<(
+        Entity,
+        &mut Bot,
+        &mut PlayerInput,
+        &Paddle,
+        &PaddleMode,
+        &PaddleAmmo,
+        &GlobalTransform,
+    )>,
+    core_q: Query<&GlobalTransform>,
+    ball_q: Query<(&GlobalTransform, &MoveDirection, Has<MovementPaused>), With<Ball>>,
+    enemy_q: Query<&GlobalTransform, With<Enemy>>,
+    mut reflected_r: EventReader<BallReflected>,
+    time: Res<Time>,
+) {
+    let reflected: Vec<_> = reflected_r.read().map(|ev| ev.paddle_e).collect();
+    for (paddle_e, mut bot, mut input, paddle, mode, ammo, paddle_t) in &mut bot_q {
+        let Ok(core_t) = core_q.get(paddle.core_e) else {
+            continue;
+        };
+        let core_pos = core_t.translation().truncate();
+        let angle = (paddle_t.translation().truncate() - core_pos).to_angle();
+        let turned = bot.prev_angle.map_or(0., |prev| {
+            Rot2::radians(prev).angle_to(Rot2::radians(angle))
+        });
+        bot.prev_angle = Some(angle);
+
+        bot.since_reflect_s += time.delta_secs();
+        if reflected.contains(&paddle_e) {
+            bot.since_reflect_s = 0.;
+        }
+
+        let ball = ball_q.iter().next();
+        let ball_offset = ball.map(|(t, ..)| t.translation().truncate() - core_pos);
+        // heading to the rail from the inside
+        let ball_incoming = bot.strategy.track_ball
+            && ball.is_some_and(|(t, dir, paused)| {
+                let offset = t.translation().truncate() - core_pos;
+                !paused && offset.length() < PADDLE_RADIUS && offset.dot(dir.0) > 0.
+            });
+        let nearest_enemy = enemy_q
+            .iter()
+            .map(|t| t.translation().truncate() - core_pos)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+        // start a cycle when it's needed
+        if bot.spin == 0. {
+            if ball.is_none() || bot.since_reflect_s > RECALL_AFTER_S {
+                bot.spin = RECALL_SPIN_ANGLE;
+                bot.since_reflect_s = 0.;
+            } else if bot.strategy.ammo_cycles
+                && ammo.ammo() == 0
+                && !matches!(mode, PaddleMode::Captured { .. })
+            {
+                bot.spin = AMMO_SPIN_ANGLE;
+            }
+        }
+
+        let target_angle = if bot.spin != 0. {
+            let remaining = bot.spin - turned;
+            // stop once the remaining turn flips its sign
+            bot.spin = if remaining * bot.spin > 0. {
+                remaining
+            } else {
+                0.
+            };
+            angle + SPIN_LEAD_ANGLE.copysign(bot.spin)
+        } else if let Some(offset) = ball_offset.filter(|_| ball_incoming) {
+            offset.to_angle()
+        } else if let Some(enemy) = nearest_enemy {
+            enemy.to_angle()
+        } else {
+            ball_offset.map_or(angle, Vec2::to_angle)
+        };
+        input.set_axis_pair(
+            &PlayerAction::AimGamepad,
+            Rot2::radians(target_angle) * Vec2::X,
+        );
+
+        let aimed_at_enemy = nearest_enemy.is_some_and(|enemy| {
+            Rot2::radians(angle)
+                .angle_to(Rot2::radians(enemy.to_angle()))
+                .abs()
+                < SHOOT_ANGLE
+        });
+        if bot.strategy.shoot && aimed_at_enemy && bot.spin == 0. && ammo.ammo() > 0 {
+            input.press(&PlayerAction::Shoot);
+        } else {
+            input.release(&PlayerAction::Shoot);
+        }
+
+        // release the captured ball towards enemies
+        if matches!(mode, PaddleMode::Captured { .. })
+            && (aimed_at_enemy || nearest_enemy.is_none())
+        {
+            input.press(&PlayerAction::TogglePaddleMode);
+        } else {
+            input.release(&PlayerAction::TogglePaddleMode);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Turns the physics contacts into gameplay collisions for the ball, projectiles and cores.
+//!
+//! The ball and projectiles are kinematic bodies with swept CCD, so fast ones don't tunnel
+//! through thin walls, and each contact is reported once when it starts.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::ext::QuatExt;
+
+use super::spawn::{ball::Ball, level::Core, projectile::Projectile};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<BallCollision>()
+        .add_event::<ProjectileCollision>()
+        .add_event::<CoreCollision>()
+        .add_systems(Update, sort_collisions.in_set(CollisionSet));
+}
+
+/// Physics layers, each only collides with the layers it has gameplay with.
+#[derive(PhysicsLayer, Debug, Clone, Copy, Default)]
+pub enum GameLayer {
+    #[default]
+    Default,
+    Paddle,
+    Ball,
+    Enemy,
+    PlayerProjectile,
+    EnemyProjectile,
+    Core,
+    /// Arena walls, obstacles and solid hazards.
+    Wall,
+    /// Sensor hazards the ball passes through, like portals and bonus lanes.
+    Hazard,
+}
+
+impl GameLayer {
+    /// Layers this one collides with, kept symmetric so both sides of a pair agree.
+    fn filters(self) -> &'static [GameLayer] {
+        use GameLayer::*;
+        match self {
+            Default => &[],
+            Paddle => &[Ball, EnemyProjectile],
+            Ball => &[
+                Paddle,
+                Enemy,
+                PlayerProjectile,
+                EnemyProjectile,
+                Core,
+                Wall,
+                Hazard,
+            ],
+            Enemy => &[Ball, PlayerProjectile, Core],
+            PlayerProjectile => &[Ball, Enemy, Wall],
+            EnemyProjectile => &[Ball, Paddle, Core],
+            Core => &[Ball, Enemy, EnemyProjectile],
+            Wall => &[Ball, PlayerProjectile],
+            Hazard => &[Ball],
+        }
+    }
+
+    pub fn layers(self) -> CollisionLayers {
+        let filters = self
+            .filters()
+            .iter()
+            .fold(0, |bits, layer| bits | layer.to_bits());
+        CollisionLayers::new(self, LayerMask(filters))
+    }
+}
+
+/// Systems reading the typed collision events run after this.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct CollisionSet;
+
+#[derive(Event, Debug)]
+pub struct BallCollision {
+    pub ball_e: Entity,
+    pub other_e: Entity,
+    /// Contact point on the surface of the other collider.
+    pub point: Vec2,
+    /// Surface normal at the contact point, pointing towards the ball.
+    pub normal: Vec2,
+}
+
+#[derive(Event, Debug)]
+pub struct ProjectileCollision {
+    pub projectile_e: Entity,
+    pub other_e: Entity,
+}
+
+#[derive(Event, Debug)]
+pub struct CoreCollision {
+    pub core_e: Entity,
+    pub other_e: Entity,
+}
+
+fn sort_collisions(
+    mut started_r: EventReader<CollisionStarted>,
+    ball_q: Query<&GlobalTransform, With<Ball>>,
+    projectile_q: Query<(), With<Projectile>>,
+    core_q: Query<(), With<Core>>,
+    collider_q: Query<(&Collider, &GlobalTransform)>,
+    mut ball_w: EventWriter<BallCollision>,
+    mut projectile_w: EventWriter<ProjectileCollision>,
+    mut core_w: EventWriter<CoreCollision>,
+) {
+    for &CollisionStarted(e1, e2) in started_r.read() {
+        for (e, other_e) in [(e1, e2), (e2, e1)] {
+            if let Ok(ball_t) = ball_q.get(e) {
+                let ball_pos = ball_t.translation().truncate();
+                let Ok((collider, other_t)) = collider_q.get(other_e) else {
+                    continue;
+                };
+                let (_, rotation, translation) = other_t.to_scale_rotation_translation();
+                let (point, inside) = collider.project_point(
+                    Position(translation.truncate()),
+                    Rotation::radians(rotation.z_angle_rad()),
+                    ball_pos,
+                    false,
+                );
+                let normal = (ball_pos - point).normalize_or_zero();
+                ball_w.write(BallCollision {
+                    ball_e: e,
+                    other_e,
+                    point,
+                    normal: if inside { -normal } else { normal },
+                });
+            }
+            if projectile_q.contains(e) {
+                projectile_w.write(ProjectileCollision {
+                    projectile_e: e,
+                    other_e,
+                });
+            }
+            if core_q.contains(e) {
+                core_w.write(CoreCollision { core_e: e, other_e });
+            }
+        }
+    }
+}
@@ -1,89 +1,230 @@
-use avian2d::prelude::*;
 use bevy::prelude::*;
-use bevy_enoki::{ParticleEffectHandle, prelude::OneShot};
+use bevy_enoki::ParticleEffectHandle;
 use bevy_trauma_shake::Shakes;
 use bevy_tweening::AssetAnimator;
 
 use crate::{
-    ext::{EventReaderExt, QuatExt},
+    AppSet,
+    ext::QuatExt,
     screen::{NextTransitionedState, Screen, in_game_state},
-    theme::palette::{COL_ENEMY_FLASH, COL_GEARS_DISABLED},
+    theme::palette::{COL_ENEMY_FLASH, COL_GEARS, COL_GEARS_DISABLED},
 };
 
 use super::{
     assets::ParticleAssets,
+    collision::{CollisionSet, CoreCollision},
+    gesture::{Gesture, GesturePerformed},
     gun::ProjectileDespawn,
+    input::{PlayerAction, PlayerInput},
+    mode::RunRules,
     movement::MovementPaused,
+    pickup::CoreShield,
+    player::{Multiplayer, Player},
+    pool::{PoolCommandsExt, PoolKey},
     spawn::{
-        enemy::{DespawnEnemy, Enemy},
+        enemy::{DespawnEnemy, Enemy, EnemyKind},
         level::{AMMO_FILL_RADIUS, AmmoFill, Core, Health, RotateWithPaddle},
-        paddle::{PADDLE_RADIUS, PaddleAmmo, PaddleRotation},
+        paddle::{PADDLE_RADIUS, Paddle, PaddleAmmo, PaddleRotation},
         projectile::{Projectile, ProjectileTarget},
     },
+    time::{Cooldown, DilateTime, process_cooldown},
     tween::{
         get_relative_color_material_color_tween, get_relative_scale_anim,
         get_relative_sprite_color_anim,
     },
+    versus::VersusResult,
 };
 
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
-    app.add_event::<TakeDamage>().add_systems(
-        Update,
-        (
-            handle_collisions,
-            rotate_gears,
-            take_damage,
-            update_ammo_fill,
-            clear_paddle_radius_on_dmg,
-        )
-            .run_if(in_game_state),
-    );
+    app.add_observer(end_game)
+        .add_event::<TakeDamage>()
+        .add_event::<CoreDamaged>()
+        .add_event::<RepairGear>()
+        .add_event::<CorePulse>()
+        .add_systems(
+            Update,
+            (
+                process_input.in_set(AppSet::ProcessInput),
+                handle_collisions.after(CollisionSet),
+                rotate_gears,
+                take_damage,
+                repair_gear,
+                update_ammo_fill,
+                clear_paddle_radius,
+                near_death_slow_motion,
+                process_cooldown::<NearDeathSlowMotion>,
+                process_cooldown::<CorePulse>,
+            )
+                .run_if(in_game_state),
+        );
+}
+
+pub const REPAIR_AMMO_COST: usize = 30;
+pub const CORE_PULSE_COOLDOWN_S: f32 = 25.;
+
+/// Distance from the core at which a threat triggers the last gear slow motion.
+const NEAR_DEATH_DISTANCE: f32 = PADDLE_RADIUS * 0.75;
+
+/// Hit of the core, it's blocked by the shield or when the rules disable core damage.
+#[derive(Event, Debug)]
+pub struct TakeDamage(pub Entity, pub DamageSource);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    Enemy(EnemyKind),
+    EnemyProjectile,
+    /// Ball launched by the opponent in versus.
+    Ball,
 }
 
-#[derive(Event, Default)]
-pub struct TakeDamage;
+/// The core lost a gear.
+#[derive(Event, Debug)]
+pub struct CoreDamaged(pub Entity, pub DamageSource);
+
+/// Re-enable the most recently disabled gear of the core.
+#[derive(Event, Debug)]
+pub struct RepairGear(pub Entity);
+
+/// Clear everything inside the paddle radius of the core without taking damage.
+#[derive(Event, Debug)]
+pub struct CorePulse(pub Entity);
+
+/// The last gear of the core has been disabled.
+#[derive(Event, Debug)]
+pub struct CoreDestroyed(pub Entity);
+
+struct NearDeathSlowMotion;
+
+fn process_input(
+    mut gesture_r: EventReader<GesturePerformed>,
+    mut paddle_q: Query<(&Paddle, &mut PaddleAmmo, &PlayerInput)>,
+    rot_q: Query<&PaddleRotation>,
+    core_q: Query<(&Core, &Health, Has<Cooldown<CorePulse>>)>,
+    mut repair_gear_w: EventWriter<RepairGear>,
+    mut core_pulse_w: EventWriter<CorePulse>,
+    mut cmd: Commands,
+    mut shake: Shakes,
+) {
+    // players shouldn't pay for the same gear twice
+    let mut repaired: Vec<Entity> = Vec::new();
+    let damaged = |core_e: Entity, repaired: &Vec<Entity>| {
+        core_q.get(core_e).is_ok_and(|(core, hp, _)| {
+            core.gear_entities.len() - hp.0 as usize
+                > repaired.iter().filter(|e| **e == core_e).count()
+        })
+    };
+    let mut pulses = Vec::new();
+    for (paddle, mut ammo, input) in &mut paddle_q {
+        if input.just_pressed(&PlayerAction::RepairGear) && damaged(paddle.core_e, &repaired) {
+            if ammo.ammo() >= REPAIR_AMMO_COST {
+                ammo.offset(-(REPAIR_AMMO_COST as isize));
+                repair_gear_w.write(RepairGear(paddle.core_e));
+                repaired.push(paddle.core_e);
+            } else {
+                shake.add_trauma(0.2);
+            }
+        }
+        if input.just_pressed(&PlayerAction::CorePulse) {
+            pulses.push(paddle.core_e);
+        }
+    }
+
+    for ev in gesture_r.read() {
+        let Some(core_e) = rot_q
+            .get(ev.entity)
+            .ok()
+            .and_then(|rot| paddle_q.get(rot.paddle_e).ok())
+            .map(|(paddle, ..)| paddle.core_e)
+        else {
+            continue;
+        };
+        match ev.gesture {
+            Gesture::RepairGear if damaged(core_e, &repaired) => {
+                repair_gear_w.write(RepairGear(core_e));
+                repaired.push(core_e);
+                shake.add_trauma(0.3);
+            }
+            Gesture::CorePulse => pulses.push(core_e),
+            _ => {}
+        }
+    }
+
+    pulses.dedup();
+    for core_e in pulses {
+        if core_q.get(core_e).is_ok_and(|(.., cooldown)| cooldown) {
+            shake.add_trauma(0.2);
+        } else {
+            core_pulse_w.write(CorePulse(core_e));
+            cmd.entity(core_e)
+                .try_insert(Cooldown::<CorePulse>::new(CORE_PULSE_COOLDOWN_S));
+        }
+    }
+}
 
 fn handle_collisions(
-    core_q: Query<&CollidingEntities, With<Core>>,
-    enemy_q: Query<(&Enemy, &GlobalTransform)>,
+    mut collision_r: EventReader<CoreCollision>,
+    enemy_q: Query<&Enemy>,
     mut taken_dmg_w: EventWriter<TakeDamage>,
     mut despawn_enemy_w: EventWriter<DespawnEnemy>,
 ) {
-    for coll in &core_q {
-        for coll_e in coll.iter().filter(|e| enemy_q.contains(**e)) {
-            taken_dmg_w.write_default();
-            despawn_enemy_w.write(DespawnEnemy(*coll_e));
+    for ev in collision_r.read() {
+        if let Ok(enemy) = enemy_q.get(ev.other_e) {
+            taken_dmg_w.write(TakeDamage(ev.core_e, DamageSource::Enemy(enemy.kind)));
+            despawn_enemy_w.write(DespawnEnemy(ev.other_e));
         }
     }
 }
 
 fn rotate_gears(
-    paddle_rot_q: Query<&Transform, With<PaddleRotation>>,
+    paddle_rot_q: Query<(Entity, &Transform, &PaddleRotation)>,
+    paddle_q: Query<(&Paddle, &Player)>,
+    core_q: Query<Entity, With<Core>>,
+    children_q: Query<&Children>,
     mut gear_q: Query<
         (&mut Transform, &RotateWithPaddle),
         (Without<PaddleRotation>, Without<MovementPaused>),
     >,
 ) {
-    if let Some(paddle_t) = paddle_rot_q.iter().next() {
-        for (mut gear_t, gear) in &mut gear_q {
-            gear_t.rotation = Quat::from_rotation_z(
-                (gear.offset.as_radians() + paddle_t.rotation.z_angle_rad())
-                    * (if gear.invert { 1. } else { -1. })
-                    * gear.multiplier,
-            );
+    let mut rotate = |e: Entity, paddle_t: &Transform| {
+        for child_e in children_q.iter_descendants(e) {
+            if let Ok((mut gear_t, gear)) = gear_q.get_mut(child_e) {
+                gear_t.rotation = Quat::from_rotation_z(
+                    (gear.offset.as_radians() + paddle_t.rotation.z_angle_rad())
+                        * (if gear.invert { 1. } else { -1. })
+                        * gear.multiplier,
+                );
+            }
+        }
+    };
+
+    for (rot_e, paddle_t, _) in &paddle_rot_q {
+        // paddle wheels
+        rotate(rot_e, paddle_t);
+    }
+    for core_e in &core_q {
+        // gears follow the first player guarding the core
+        let first_player_rot = paddle_rot_q
+            .iter()
+            .filter_map(|(_, t, rot)| {
+                let (paddle, player) = paddle_q.get(rot.paddle_e).ok()?;
+                (paddle.core_e == core_e).then_some((t, player.0))
+            })
+            .min_by_key(|(_, player)| *player);
+        if let Some((paddle_t, _)) = first_player_rot {
+            rotate(core_e, paddle_t);
         }
     }
 }
 
 fn update_ammo_fill(
-    ammo_q: Query<&PaddleAmmo, Changed<PaddleAmmo>>,
-    ammo_fill_q: Query<Entity, With<AmmoFill>>,
+    ammo_q: Query<(&PaddleAmmo, &Player), Changed<PaddleAmmo>>,
+    ammo_fill_q: Query<(Entity, &Player), With<AmmoFill>>,
     mut cmd: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
 ) {
-    if let Some(ammo) = ammo_q.iter().next() {
-        for e in &ammo_fill_q {
+    for (ammo, player) in &ammo_q {
+        for (e, _) in ammo_fill_q.iter().filter(|(_, p)| *p == player) {
             cmd.entity(e)
                 .try_insert(Mesh2d(meshes.add(CircularSegment::from_turns(
                     AMMO_FILL_RADIUS,
@@ -96,14 +237,22 @@ fn update_ammo_fill(
 
 fn take_damage(
     mut ev_r: EventReader<TakeDamage>,
-    mut core_q: Query<(&mut Core, &mut Health)>,
+    mut core_q: Query<(&mut Core, &mut Health, Has<Cooldown<CoreShield>>)>,
+    rules: Res<RunRules>,
+    mut core_damaged_w: EventWriter<CoreDamaged>,
     mut cmd: Commands,
-    mut next: ResMut<NextTransitionedState>,
     mut shake: Shakes,
 ) -> Result {
-    let (mut core, mut hp) = core_q.single_mut()?;
-    if !ev_r.is_empty() {
-        ev_r.clear();
+    // a core takes at most a single hit per frame
+    let mut hits: Vec<_> = ev_r.read().map(|ev| (ev.0, ev.1)).collect();
+    hits.sort_by_key(|(core_e, _)| *core_e);
+    hits.dedup_by_key(|(core_e, _)| *core_e);
+    for (core_e, source) in hits {
+        let (mut core, mut hp, shielded) = core_q.get_mut(core_e)?;
+        if shielded || !rules.core_damage {
+            shake.add_trauma(0.3);
+            continue;
+        }
         shake.add_trauma(0.9);
 
         let (e, active) = core
@@ -119,15 +268,68 @@ fn take_damage(
         ));
 
         hp.0 -= 1;
+        core_damaged_w.write(CoreDamaged(core_e, source));
         if hp.0 == 0 {
-            next.set(Screen::GameOver);
+            cmd.trigger(CoreDestroyed(core_e));
         }
     }
     Ok(())
 }
 
-fn clear_paddle_radius_on_dmg(
+fn end_game(
+    trigger: Trigger<CoreDestroyed>,
+    paddle_q: Query<(&Paddle, &Player)>,
+    core_q: Query<&Health, With<Core>>,
+    multiplayer: Res<Multiplayer>,
+    mut next: ResMut<NextTransitionedState>,
+    mut cmd: Commands,
+) {
+    if *multiplayer != Multiplayer::Versus {
+        next.set(Screen::GameOver);
+        return;
+    }
+
+    // last one standing wins
+    let winner = paddle_q
+        .iter()
+        .find(|(paddle, _)| {
+            paddle.core_e != trigger.event().0 && core_q.get(paddle.core_e).is_ok_and(|hp| hp.0 > 0)
+        })
+        .map(|(_, player)| *player);
+    cmd.insert_resource(VersusResult { winner });
+    next.set(Screen::VersusResults);
+}
+
+fn repair_gear(
+    mut ev_r: EventReader<RepairGear>,
+    mut core_q: Query<(&mut Core, &mut Health)>,
+    mut cmd: Commands,
+) -> Result {
+    for ev in ev_r.read() {
+        let (mut core, mut hp) = core_q.get_mut(ev.0)?;
+        // gears get disabled front to back
+        let Some((e, active)) = core
+            .gear_entities
+            .iter_mut()
+            .rev()
+            .find(|(_, active)| !*active)
+        else {
+            continue;
+        };
+        *active = true;
+        cmd.entity(*e).remove::<MovementPaused>().try_insert((
+            get_relative_scale_anim(Vec3::ONE, 350, Some(EaseFunction::BackOut)),
+            get_relative_sprite_color_anim(COL_GEARS, 350, None),
+        ));
+
+        hp.0 += 1;
+    }
+    Ok(())
+}
+
+fn clear_paddle_radius(
     mut ev_r: EventReader<TakeDamage>,
+    mut pulse_r: EventReader<CorePulse>,
     projectile_q: Query<(Entity, &Projectile, &GlobalTransform)>,
     enemy_q: Query<(Entity, &GlobalTransform), With<Enemy>>,
     core_q: Query<(&Core, &GlobalTransform)>,
@@ -136,18 +338,25 @@ fn clear_paddle_radius_on_dmg(
     mut cmd: Commands,
     particles: Res<ParticleAssets>,
 ) -> Result {
-    let (core, core_t) = core_q.single()?;
-    if ev_r.clear_any() {
-        for (projectile_e, ..) in projectile_q.iter().filter(|(_, p, t, ..)| {
-            p.target == ProjectileTarget::Core && t.translation().length() < PADDLE_RADIUS
-        }) {
+    let mut core_entities: Vec<_> = ev_r
+        .read()
+        .map(|ev| ev.0)
+        .chain(pulse_r.read().map(|ev| ev.0))
+        .collect();
+    core_entities.sort();
+    core_entities.dedup();
+    for core_e in core_entities {
+        let (core, core_t) = core_q.get(core_e)?;
+        let inside =
+            |t: &GlobalTransform| t.translation().distance(core_t.translation()) < PADDLE_RADIUS;
+        for (projectile_e, ..) in projectile_q
+            .iter()
+            .filter(|(_, p, t, ..)| p.target == ProjectileTarget::Core && inside(t))
+        {
             projectile_despawn_w.write(ProjectileDespawn(projectile_e));
         }
 
-        for (enemy_e, ..) in enemy_q
-            .iter()
-            .filter(|(_, t, ..)| t.translation().length() < PADDLE_RADIUS)
-        {
+        for (enemy_e, ..) in enemy_q.iter().filter(|(_, t, ..)| inside(t)) {
             despawn_enemy_w.write(DespawnEnemy(enemy_e));
         }
 
@@ -166,12 +375,47 @@ fn clear_paddle_radius_on_dmg(
         ));
 
         // particles
-        cmd.spawn((
-            particles.circle_particle_spawner(),
-            ParticleEffectHandle(particles.core_clear.clone_weak()),
-            Transform::from_translation(core_t.translation().with_z(0.51)),
-            OneShot::Despawn,
-        ));
+        cmd.spawn_particles(
+            PoolKey::CircleParticles,
+            (
+                particles.circle_particle_spawner(),
+                ParticleEffectHandle(particles.core_clear.clone_weak()),
+                Transform::from_translation(core_t.translation().with_z(0.51)),
+            ),
+        );
     }
     Ok(())
 }
+
+fn near_death_slow_motion(
+    core_q: Query<
+        (Entity, &Health, &GlobalTransform),
+        (With<Core>, Without<Cooldown<NearDeathSlowMotion>>),
+    >,
+    enemy_q: Query<&GlobalTransform, With<Enemy>>,
+    projectile_q: Query<(&Projectile, &GlobalTransform)>,
+    rules: Res<RunRules>,
+    mut dilate_time_w: EventWriter<DilateTime>,
+    mut cmd: Commands,
+) {
+    for (core_e, hp, core_t) in &core_q {
+        // runs starting on the last gear would be in slow motion all the time
+        if hp.0 != 1 || rules.gears <= 1 {
+            continue;
+        }
+
+        let is_near = |t: &GlobalTransform| {
+            t.translation().distance(core_t.translation()) < NEAR_DEATH_DISTANCE
+        };
+        let threatened = enemy_q.iter().any(is_near)
+            || projectile_q
+                .iter()
+                .any(|(p, t)| p.target == ProjectileTarget::Core && is_near(t));
+        if threatened {
+            // the last gear is about to go
+            dilate_time_w.write(DilateTime::slow_motion(0.3, 900).with_priority(2));
+            cmd.entity(core_e)
+                .try_insert(Cooldown::<NearDeathSlowMotion>::new(4.));
+        }
+    }
+}
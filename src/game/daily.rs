@@ -0,0 +1,129 @@
+//! Daily challenge, a seeded run with a rule modifier shared by everyone playing the same day.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use super::{core::CoreDestroyed, mode::GameModeDef, score::Score, spawn::enemy::EnemyKind};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<DailyRun>()
+        .init_resource::<DailyBest>()
+        .add_observer(record_daily_best);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DailyModifier {
+    NoGun,
+    DoubleSpeedBall,
+    OnlyBangBang,
+}
+
+impl DailyModifier {
+    const ALL: [DailyModifier; 3] = [
+        DailyModifier::NoGun,
+        DailyModifier::DoubleSpeedBall,
+        DailyModifier::OnlyBangBang,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DailyModifier::NoGun => "NO GUN",
+            DailyModifier::DoubleSpeedBall => "DOUBLE SPEED BALL",
+            DailyModifier::OnlyBangBang => "ONLY BANG BANGS",
+        }
+    }
+
+    pub fn apply(&self, def: &mut GameModeDef) {
+        match self {
+            DailyModifier::NoGun => def.gun_enabled = false,
+            DailyModifier::DoubleSpeedBall => def.ball_speed_mult = 2.,
+            DailyModifier::OnlyBangBang => def.enemy_kind = Some(EnemyKind::BangBang),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DailyChallenge {
+    /// Days since the unix epoch (UTC).
+    pub day: i64,
+    pub seed: u64,
+    pub modifier: DailyModifier,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self::for_day((secs / 86_400) as i64)
+    }
+
+    fn for_day(day: i64) -> Self {
+        // splitmix64 so neighbouring days get unrelated seeds
+        let mut seed = (day as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        seed = (seed ^ (seed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        seed = (seed ^ (seed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        seed ^= seed >> 31;
+        Self {
+            day,
+            seed,
+            modifier: DailyModifier::ALL[(seed % DailyModifier::ALL.len() as u64) as usize],
+        }
+    }
+
+    /// `YYYY-MM-DD` of the challenge.
+    pub fn date_label(&self) -> String {
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = self.day + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+/// Daily challenge of the current run, `None` outside of the daily mode.
+#[derive(Resource, Debug, Default)]
+pub struct DailyRun(pub Option<DailyChallenge>);
+
+impl DailyRun {
+    pub fn modifier(&self) -> Option<DailyModifier> {
+        self.0.map(|daily| daily.modifier)
+    }
+}
+
+/// Best score of the latest daily challenge played with the active profile.
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DailyBest {
+    pub day: i64,
+    pub score: usize,
+}
+
+impl DailyBest {
+    pub fn score_for(&self, daily: &DailyChallenge) -> Option<usize> {
+        (self.day == daily.day).then_some(self.score)
+    }
+}
+
+fn record_daily_best(
+    _trigger: Trigger<CoreDestroyed>,
+    daily: Res<DailyRun>,
+    score: Res<Score>,
+    mut best: ResMut<DailyBest>,
+) {
+    let Some(daily) = daily.0 else {
+        return;
+    };
+    if best.score_for(&daily).is_none_or(|best| score.0 > best) {
+        *best = DailyBest {
+            day: daily.day,
+            score: score.0,
+        };
+    }
+}
@@ -0,0 +1,348 @@
+//! Paddle gestures recognized from its [`AccumulatedRotation`].
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::screen::in_game_state;
+
+use super::movement::AccumulatedRotation;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GestureBindings>()
+        .add_event::<GesturePerformed>()
+        .add_systems(Update, recognize_gestures.run_if(in_game_state));
+}
+
+/// Angular speed (rad/s) under which the paddle is considered to be still.
+const IDLE_SPEED: f32 = 3.;
+/// Turns are reset when the paddle is still for this long.
+const TURN_IDLE_RESET_S: f32 = 0.05;
+
+/// Ability a gesture is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Gesture {
+    RecallBall,
+    GainAmmo,
+    RepairGear,
+    CorePulse,
+    SwitchWeapon,
+    SteadyAim,
+}
+
+/// Shape of a gesture, angles are in radians and negative for CW.
+#[derive(Debug, Clone)]
+pub enum GesturePattern {
+    /// Uninterrupted turn by the given angle.
+    Turn { angle: f32 },
+    /// Turn by the given angle performed in a short time.
+    Flick { angle: f32, max_duration_s: f32 },
+    /// Consecutive turns, each of them has to be finished before the next one starts.
+    Sequence {
+        turns: Vec<f32>,
+        max_duration_s: f32,
+    },
+    /// Back-and-forth swings, each of them at least `min_angle` wide.
+    Wiggle {
+        swings: u8,
+        min_angle: f32,
+        max_duration_s: f32,
+    },
+    /// No rotation for the given time.
+    HoldStill { duration_s: f32 },
+}
+
+#[derive(Resource, Debug)]
+pub struct GestureBindings(pub Vec<(Gesture, GesturePattern)>);
+
+impl Default for GestureBindings {
+    fn default() -> Self {
+        Self(vec![
+            (
+                Gesture::RecallBall,
+                GesturePattern::Turn {
+                    angle: -720f32.to_radians(),
+                },
+            ),
+            (
+                Gesture::GainAmmo,
+                GesturePattern::Turn {
+                    angle: 360f32.to_radians(),
+                },
+            ),
+            (
+                Gesture::RepairGear,
+                GesturePattern::Sequence {
+                    turns: [-360f32, 360., -360.].map(f32::to_radians).to_vec(),
+                    max_duration_s: 4.,
+                },
+            ),
+            (
+                Gesture::CorePulse,
+                GesturePattern::Wiggle {
+                    swings: 6,
+                    min_angle: 40f32.to_radians(),
+                    max_duration_s: 1.5,
+                },
+            ),
+            (
+                Gesture::SwitchWeapon,
+                // clockwise, as ammo is gained by spinning the other way and far more often
+                GesturePattern::Flick {
+                    angle: -180f32.to_radians(),
+                    max_duration_s: 0.25,
+                },
+            ),
+            (
+                Gesture::SteadyAim,
+                GesturePattern::HoldStill { duration_s: 0.8 },
+            ),
+        ])
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct GesturePerformed {
+    pub gesture: Gesture,
+    /// Entity with the [`GestureRecognizer`] which performed the gesture.
+    pub entity: Entity,
+}
+
+#[derive(Component, Debug, Default)]
+pub struct GestureRecognizer {
+    prev_rot: Option<f32>,
+    idle_s: f32,
+    states: HashMap<Gesture, GestureState>,
+}
+
+impl GestureRecognizer {
+    /// Progress (0-1) of the current attempt at the gesture.
+    pub fn progress(&self, gesture: Gesture) -> f32 {
+        self.states.get(&gesture).map_or(0., |s| s.progress)
+    }
+}
+
+#[derive(Debug, Default)]
+struct GestureState {
+    /// Rotation the current turn started at.
+    start: f32,
+    /// Furthest rotation of the current swing.
+    extreme: f32,
+    /// Sign of the current swing, 0 before the first one.
+    dir: f32,
+    stage: usize,
+    elapsed: f32,
+    progress: f32,
+}
+
+impl GestureState {
+    fn new(rotation: f32) -> Self {
+        Self {
+            start: rotation,
+            extreme: rotation,
+            ..default()
+        }
+    }
+
+    fn reset(&mut self, rotation: f32) {
+        *self = Self::new(rotation);
+    }
+
+    /// Advance the state and return whether the gesture has been performed.
+    fn step(&mut self, pattern: &GesturePattern, rotation: f32, dt: f32, idle_s: f32) -> bool {
+        match pattern {
+            GesturePattern::Turn { angle } => {
+                if idle_s >= TURN_IDLE_RESET_S {
+                    self.reset(rotation);
+                }
+                self.turn(*angle, rotation) >= 1.
+            }
+            GesturePattern::Flick {
+                angle,
+                max_duration_s,
+            } => {
+                if self.elapsed > *max_duration_s {
+                    self.reset(rotation);
+                }
+                let turned = self.turn(*angle, rotation);
+                if turned > 0. {
+                    self.elapsed += dt;
+                }
+                turned >= 1.
+            }
+            GesturePattern::Sequence {
+                turns,
+                max_duration_s,
+            } => {
+                if self.stage > 0 {
+                    self.elapsed += dt;
+                    if self.elapsed > *max_duration_s {
+                        self.reset(rotation);
+                    }
+                }
+                let Some(angle) = turns.get(self.stage) else {
+                    self.reset(rotation);
+                    return false;
+                };
+                let turned = (rotation - self.start) / angle;
+                if turned >= 1. {
+                    self.stage += 1;
+                    self.start = rotation;
+                } else if turned < -0.25 {
+                    // turning the wrong way
+                    self.reset(rotation);
+                }
+                self.progress = (self.stage as f32 + turned.max(0.)) / turns.len() as f32;
+                if self.stage == turns.len() {
+                    self.reset(rotation);
+                    return true;
+                }
+                false
+            }
+            GesturePattern::Wiggle {
+                swings,
+                min_angle,
+                max_duration_s,
+            } => {
+                if self.stage > 0 {
+                    self.elapsed += dt;
+                    if self.elapsed > *max_duration_s {
+                        self.reset(rotation);
+                    }
+                }
+                if self.dir == 0. {
+                    if (rotation - self.start).abs() >= *min_angle {
+                        self.dir = (rotation - self.start).signum();
+                        self.extreme = rotation;
+                    }
+                } else if (rotation - self.extreme) * self.dir > 0. {
+                    self.extreme = rotation;
+                } else if (self.extreme - rotation) * self.dir >= *min_angle {
+                    // swung back
+                    self.stage += 1;
+                    self.dir = -self.dir;
+                    self.extreme = rotation;
+                }
+                self.progress = self.stage as f32 / *swings as f32;
+                if self.stage >= *swings as usize {
+                    self.reset(rotation);
+                    return true;
+                }
+                false
+            }
+            GesturePattern::HoldStill { duration_s } => {
+                self.progress = (idle_s / duration_s).min(1.);
+                idle_s >= *duration_s && idle_s - dt < *duration_s
+            }
+        }
+    }
+
+    /// Update a turn towards the given angle and return its progress,
+    /// resetting the state once it's done.
+    fn turn(&mut self, angle: f32, rotation: f32) -> f32 {
+        let turned = (rotation - self.start) / angle;
+        if turned < 0. {
+            // moving the other way restarts the turn
+            self.reset(rotation);
+            return 0.;
+        }
+        self.progress = turned.min(1.);
+        if turned >= 1. {
+            self.reset(rotation);
+        }
+        turned
+    }
+}
+
+fn recognize_gestures(
+    mut recognizer_q: Query<(Entity, &mut GestureRecognizer, &AccumulatedRotation)>,
+    bindings: Res<GestureBindings>,
+    mut gesture_w: EventWriter<GesturePerformed>,
+    time: Res<Time<Real>>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0. {
+        return;
+    }
+
+    for (e, mut recognizer, acc) in &mut recognizer_q {
+        let rotation = acc.rotation;
+        let delta = rotation - recognizer.prev_rot.unwrap_or(rotation);
+        recognizer.prev_rot = Some(rotation);
+        if delta.abs() / dt < IDLE_SPEED {
+            recognizer.idle_s += dt;
+        } else {
+            recognizer.idle_s = 0.;
+        }
+
+        let idle_s = recognizer.idle_s;
+        for (gesture, pattern) in &bindings.0 {
+            let state = recognizer
+                .states
+                .entry(*gesture)
+                .or_insert_with(|| GestureState::new(rotation));
+            if state.step(pattern, rotation, dt, idle_s) {
+                gesture_w.write(GesturePerformed {
+                    gesture: *gesture,
+                    entity: e,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    const DT: f32 = 1. / 60.;
+
+    /// Feeds a rotation per frame and returns how many times the gesture has been performed.
+    fn perform(pattern: &GesturePattern, rotations: impl IntoIterator<Item = f32>) -> usize {
+        let mut state = GestureState::new(0.);
+        let mut prev_rot = 0.;
+        let mut idle_s = 0.;
+        rotations
+            .into_iter()
+            .filter(|&rotation| {
+                if ((rotation - prev_rot) / DT).abs() < IDLE_SPEED {
+                    idle_s += DT;
+                } else {
+                    idle_s = 0.;
+                }
+                prev_rot = rotation;
+                state.step(pattern, rotation, DT, idle_s)
+            })
+            .count()
+    }
+
+    const FLICK: GesturePattern = GesturePattern::Flick {
+        angle: -PI,
+        max_duration_s: 0.25,
+    };
+
+    #[test]
+    fn fast_half_turn_is_a_flick() {
+        let rotations = (1..=12).map(|i| i as f32 * -PI / 10.);
+        assert_eq!(perform(&FLICK, rotations), 1);
+    }
+
+    #[test]
+    fn slow_half_turn_is_not_a_flick() {
+        let rotations = (1..=40).map(|i| i as f32 * -PI / 30.);
+        assert_eq!(perform(&FLICK, rotations), 0);
+    }
+
+    #[test]
+    fn hold_still_fires_once() {
+        let pattern = GesturePattern::HoldStill { duration_s: 0.8 };
+        assert_eq!(perform(&pattern, [0.; 120]), 1);
+    }
+
+    #[test]
+    fn moving_interrupts_hold_still() {
+        let pattern = GesturePattern::HoldStill { duration_s: 0.8 };
+        let rotations = (0..120).map(|i| (i / 30) as f32);
+        assert_eq!(perform(&pattern, rotations), 0);
+    }
+}
@@ -1,4 +1,3 @@
-use avian2d::prelude::*;
 use bevy::prelude::*;
 use bevy_enoki::prelude::*;
 use bevy_trauma_shake::Shakes;
@@ -14,30 +13,42 @@ use crate::{
 use super::{
     assets::ParticleAssets,
     ball::MaxBallSpeedFactor,
-    core::TakeDamage,
+    collision::{CollisionSet, ProjectileCollision},
+    core::{DamageSource, TakeDamage},
+    hazard::BarrierHit,
     input::{PlayerAction, PlayerInput},
-    movement::{Damping, Impulse, MoveDirection, Speed, Velocity},
+    mode::gun_enabled,
+    movement::{Damping, Impulse, MoveDirection},
     paddle::PaddleKnockback,
+    parry::{ParryProjectile, ParryWindow},
+    pickup::MultiShot,
+    pool::{PoolCommandsExt, PoolKey},
     spawn::{
-        enemy::{DespawnEnemy, Enemy, EnemyGunBarrel, Shielded},
+        enemy::{DespawnEnemy, Enemy, EnemyGunBarrel, EnemyKilled, KillSource, Shielded},
+        hazard::Barrier,
         level::{Core, Health},
         paddle::{Paddle, PaddleAmmo},
         projectile::{Projectile, ProjectileTarget},
     },
     time::{Cooldown, process_cooldown},
     tween::{DespawnOnTweenCompleted, get_relative_translation_tween},
+    versus::{Garbage, SendGarbage},
+    weapon::{SteadyAim, Weapon, WeaponDef},
 };
 
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
     app.add_event::<ProjectileDespawn>()
+        .add_event::<ShotHit>()
         .add_systems(Last, despawn_projectile_on_hit)
         .add_systems(
             Update,
             (
-                fire_player_gun,
+                (start_charging_shot, charge_shot, fire_player_gun)
+                    .chain()
+                    .run_if(gun_enabled),
                 fire_enemy_gun,
-                handle_collisions,
+                handle_collisions.after(CollisionSet),
                 process_cooldown::<NoAmmoShake>,
                 process_cooldown::<PaddleAmmo>,
                 process_cooldown::<EnemyGunBarrel>,
@@ -48,85 +59,234 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Event, Debug)]
 pub struct ProjectileDespawn(pub Entity);
 
+/// A player shot hit its first enemy.
+#[derive(Event, Debug)]
+pub struct ShotHit;
+
 struct NoAmmoShake;
 
+pub const CHARGE_DURATION_S: f32 = 0.8;
+
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct ChargingShot(Timer);
+
+fn start_charging_shot(
+    paddle_q: Query<
+        (Entity, &PaddleAmmo, &PlayerInput),
+        (
+            With<Paddle>,
+            Without<ChargingShot>,
+            Without<Cooldown<PaddleAmmo>>,
+        ),
+    >,
+    mut cmd: Commands,
+) {
+    for (e, ammo, input) in &paddle_q {
+        let charge_pressed = input.just_pressed(&PlayerAction::ChargeShot);
+        let shoot_pressed = input.just_pressed(&PlayerAction::Shoot);
+        // holding shoot at full ammo charges too
+        if (charge_pressed && ammo.ammo() >= WeaponDef::charged_shot().ammo_cost)
+            || (shoot_pressed && ammo.factor() >= 1.)
+        {
+            cmd.entity(e).try_insert(ChargingShot(Timer::from_seconds(
+                CHARGE_DURATION_S,
+                TimerMode::Once,
+            )));
+        }
+    }
+}
+
+fn charge_shot(
+    mut paddle_q: Query<(
+        Entity,
+        &Paddle,
+        &PlayerInput,
+        &mut PaddleAmmo,
+        &mut Weapon,
+        &mut ChargingShot,
+        &GlobalTransform,
+    )>,
+    mut barrel_q: Query<&mut Transform>,
+    mut cmd: Commands,
+    mut shake: Shakes,
+    particles: Res<ParticleAssets>,
+    time: Res<Time>,
+) {
+    for (e, paddle, input, mut ammo, mut weapon, mut charging, t) in &mut paddle_q {
+        let held = input.pressed(&PlayerAction::Shoot) || input.pressed(&PlayerAction::ChargeShot);
+        charging.0.tick(time.delta());
+        if let Ok(mut barrel_t) = barrel_q.get_mut(paddle.barrel_e) {
+            barrel_t.scale = Vec2::splat(1. + 0.6 * charging.0.fraction()).extend(1.);
+        }
+        if charging.0.just_finished() {
+            shake.add_trauma(0.15);
+        }
+
+        if held {
+            continue;
+        }
+
+        cmd.entity(e).remove::<ChargingShot>();
+        cmd.entity(paddle.barrel_e)
+            .try_insert(get_relative_scale_anim(
+                Vec3::ONE,
+                150,
+                Some(EaseFunction::BackOut),
+            ));
+
+        if !charging.0.finished() {
+            // released early
+            weapon.queued = true;
+            continue;
+        }
+
+        let def = WeaponDef::charged_shot();
+        let dir = Dir2::new(t.right().truncate()).unwrap();
+        let barrel_pos = t.translation() + t.right() * 100.;
+        cmd.trigger(SpawnProjectile {
+            target: ProjectileTarget::Enemy,
+            position: barrel_pos.truncate(),
+            dir,
+            max_accuracy_spread: def.accuracy_spread,
+            speed: def.speed,
+            scale: def.scale,
+            damage: def.damage,
+            pierce: def.pierce,
+            impulse: def.impulse,
+        });
+        ammo.offset(-(def.ammo_cost as isize));
+        shake.add_trauma(def.trauma(0.));
+        cmd.entity(e)
+            .insert(Cooldown::<PaddleAmmo>::new(def.cooldown(0.)));
+        cmd.send_delayed_event(
+            PaddleKnockback {
+                paddle_e: e,
+                offset: def.knockback,
+            },
+            40,
+        );
+        cmd.spawn_particles(
+            PoolKey::CircleParticles,
+            (
+                particles.circle_particle_spawner(),
+                ParticleEffectHandle(particles.gun.clone_weak()),
+                Transform::from_translation(barrel_pos)
+                    .with_rotation(t.to_scale_rotation_translation().1)
+                    .with_scale(Vec2::splat(2.).extend(1.)),
+            ),
+        );
+    }
+}
+
 fn fire_player_gun(
     mut ammo_q: Query<
         (
             Entity,
             &Paddle,
+            &PlayerInput,
             &mut PaddleAmmo,
+            &mut Weapon,
             &GlobalTransform,
             Option<&Cooldown<NoAmmoShake>>,
+            Has<Cooldown<MultiShot>>,
+            Has<Cooldown<SteadyAim>>,
         ),
-        Without<Cooldown<PaddleAmmo>>,
+        (Without<Cooldown<PaddleAmmo>>, Without<ChargingShot>),
     >,
-    input: PlayerInput,
     mut cmd: Commands,
     mut shake: Shakes,
     particles: Res<ParticleAssets>,
     ball_speed_factor: Res<MaxBallSpeedFactor>,
 ) {
-    if input.pressed(&PlayerAction::Shoot) {
-        for (e, paddle, mut ammo, t, cooldown) in &mut ammo_q {
-            if ammo.ammo() > 0 {
-                let dir = Dir2::new(t.right().truncate()).unwrap();
-                let barrel_pos = t.translation() + t.right() * 80.;
-
-                cmd.trigger(SpawnProjectile {
-                    target: ProjectileTarget::Enemy,
-                    position: barrel_pos.truncate(),
-                    dir,
-                    max_accuracy_spread: 4.5,
-                });
-                ammo.offset(-1);
-                shake.add_trauma(0.165 - 0.08 * ball_speed_factor.0);
-                cmd.entity(e).insert(Cooldown::<PaddleAmmo>::new(
-                    0.17 - 0.08 * ball_speed_factor.0,
-                ));
-
-                // tween
-                // barrel
-                cmd.entity(paddle.barrel_e).insert(Animator::new(
-                    get_relative_translation_tween(
-                        Vec3::Y * -35.,
-                        60,
-                        Some(EaseFunction::QuadraticOut),
-                    )
-                    .then(get_relative_translation_tween(
-                        Vec3::ZERO,
-                        110,
-                        Some(EaseFunction::BackOut),
-                    )),
-                ));
-                cmd.send_delayed_event(PaddleKnockback(-8.), 40);
-
-                cmd.entity(paddle.sprite_e).insert(Animator::new(
-                    Delay::new(Duration::from_millis(40))
-                        .then(get_relative_translation_tween(
-                            Vec3::X * -8.,
-                            40,
-                            Some(EaseFunction::QuadraticOut),
-                        ))
-                        .then(get_relative_translation_tween(
-                            Vec3::ZERO,
-                            60,
-                            Some(EaseFunction::BackOut),
-                        )),
-                ));
-
-                cmd.spawn((
-                    particles.circle_particle_spawner(),
-                    ParticleEffectHandle(particles.gun.clone_weak()),
-                    Transform::from_translation(barrel_pos)
-                        .with_rotation(t.to_scale_rotation_translation().1),
-                    OneShot::Despawn,
-                ));
-            } else if cooldown.is_none() {
+    for (e, paddle, input, mut ammo, mut weapon, t, cooldown, multi_shot, steady) in &mut ammo_q {
+        let def = weapon.kind.def();
+        if weapon.burst_remaining > 0 {
+            // the whole burst has been paid for by the first shot
+            weapon.burst_remaining -= 1;
+        } else if !input.pressed(&PlayerAction::Shoot) && !weapon.queued {
+            continue;
+        } else if ammo.ammo() >= def.ammo_cost {
+            ammo.offset(-(def.ammo_cost as isize));
+            weapon.burst_remaining = def.burst_count.saturating_sub(1);
+        } else {
+            if cooldown.is_none() {
                 shake.add_trauma(0.4);
                 cmd.entity(e).insert(Cooldown::<NoAmmoShake>::new(1.));
             }
+            continue;
         }
+        weapon.queued = false;
+
+        let dir = Dir2::new(t.right().truncate()).unwrap();
+        let barrel_pos = t.translation() + t.right() * 80.;
+
+        let multi_shot_angles: &[f32] = if multi_shot { &[-12., 0., 12.] } else { &[0.] };
+        for (dir, angle) in def
+            .directions(dir)
+            .flat_map(|dir| multi_shot_angles.iter().map(move |angle| (dir, *angle)))
+        {
+            cmd.trigger(SpawnProjectile {
+                target: ProjectileTarget::Enemy,
+                position: barrel_pos.truncate(),
+                dir: Rot2::degrees(angle) * dir,
+                max_accuracy_spread: if steady { 0. } else { def.accuracy_spread },
+                speed: def.speed,
+                scale: def.scale,
+                damage: def.damage,
+                pierce: def.pierce,
+                impulse: def.impulse,
+            });
+        }
+        shake.add_trauma(def.trauma(ball_speed_factor.0));
+        cmd.entity(e)
+            .insert(Cooldown::<PaddleAmmo>::new(if weapon.burst_remaining > 0 {
+                def.burst_interval
+            } else {
+                def.cooldown(ball_speed_factor.0)
+            }));
+
+        // tween
+        // barrel
+        cmd.entity(paddle.barrel_e).insert(Animator::new(
+            get_relative_translation_tween(Vec3::Y * -35., 60, Some(EaseFunction::QuadraticOut))
+                .then(get_relative_translation_tween(
+                    Vec3::ZERO,
+                    110,
+                    Some(EaseFunction::BackOut),
+                )),
+        ));
+        cmd.send_delayed_event(
+            PaddleKnockback {
+                paddle_e: e,
+                offset: def.knockback,
+            },
+            40,
+        );
+
+        cmd.entity(paddle.sprite_e).insert(Animator::new(
+            Delay::new(Duration::from_millis(40))
+                .then(get_relative_translation_tween(
+                    Vec3::X * def.knockback,
+                    40,
+                    Some(EaseFunction::QuadraticOut),
+                ))
+                .then(get_relative_translation_tween(
+                    Vec3::ZERO,
+                    60,
+                    Some(EaseFunction::BackOut),
+                )),
+        ));
+
+        cmd.spawn_particles(
+            PoolKey::CircleParticles,
+            (
+                particles.circle_particle_spawner(),
+                ParticleEffectHandle(particles.gun.clone_weak()),
+                Transform::from_translation(barrel_pos)
+                    .with_rotation(t.to_scale_rotation_translation().1),
+            ),
+        );
     }
 }
 
@@ -150,6 +310,11 @@ fn fire_enemy_gun(
             position: (t.translation() + (rot * (Vec3::Y * 20.0))).truncate(),
             dir,
             max_accuracy_spread: 2.0,
+            speed: 250.,
+            scale: Vec2::ONE,
+            damage: 1,
+            pierce: 0,
+            impulse: 30.,
         });
         cmd.entity(barrel_e)
             .try_insert(Cooldown::<EnemyGunBarrel>::new(2.5));
@@ -194,72 +359,114 @@ fn fire_enemy_gun(
 }
 
 fn handle_collisions(
-    phys_spatial: SpatialQuery,
-    projectile_q: Query<(
-        Entity,
-        &GlobalTransform,
-        &Projectile,
-        &Velocity,
-        &MoveDirection,
-        &Speed,
+    mut collision_r: EventReader<ProjectileCollision>,
+    mut projectile_q: Query<(&mut Projectile, &MoveDirection)>,
+    mut enemy_q: Query<(
+        &Enemy,
+        &mut Health,
+        &mut Impulse,
+        Option<&Shielded>,
+        Has<Garbage>,
     )>,
-    mut enemy_q: Query<(&mut Health, &mut Impulse, Option<&Shielded>), With<Enemy>>,
-    paddle_q: Query<&GlobalTransform, With<Paddle>>,
+    paddle_q: Query<Has<ParryWindow>, With<Paddle>>,
     core_q: Query<(), With<Core>>,
-    time: Res<Time>,
+    barrier_q: Query<(), With<Barrier>>,
     mut taken_dmg_w: EventWriter<TakeDamage>,
     mut knockback_paddle_w: EventWriter<PaddleKnockback>,
     mut projectile_hit_w: EventWriter<ProjectileDespawn>,
     mut despawn_enemy_w: EventWriter<DespawnEnemy>,
+    mut enemy_killed_w: EventWriter<EnemyKilled>,
+    mut shot_hit_w: EventWriter<ShotHit>,
+    mut barrier_hit_w: EventWriter<BarrierHit>,
+    mut cmd: Commands,
 ) {
-    for (e, t, projectile, vel, move_dir, speed) in &projectile_q {
-        if (vel.velocity() - Vec2::ZERO).length() < f32::EPSILON {
-            // stationary
+    // projectiles stay around until the end of the frame, skip the rest of their hits
+    let mut despawned = Vec::new();
+    for ev in collision_r.read() {
+        let e = ev.projectile_e;
+        if despawned.contains(&e) {
             continue;
         }
+        let Ok((mut projectile, move_dir)) = projectile_q.get_mut(e) else {
+            continue;
+        };
 
-        for hit in phys_spatial.shape_hits(
-            &Collider::rectangle(projectile.size.x, projectile.size.y),
-            t.translation().truncate(),
-            0.,
-            Dir2::new(move_dir.0).expect("Non zero velocity"),
-            100,
-            &ShapeCastConfig::from_max_distance((speed.0 * 1.05) * time.delta_secs()),
-            &SpatialQueryFilter::default(),
-        ) {
-            let hit_e = hit.entity;
-            let mut despawn = false;
-            match projectile.target {
-                ProjectileTarget::Enemy => {
-                    if let Ok((mut enemy_hp, mut impulse, shielded)) = enemy_q.get_mut(hit_e) {
-                        despawn = true;
+        let hit_e = ev.other_e;
+        let mut despawn = false;
+        let target = projectile.target;
+        match target {
+            ProjectileTarget::Enemy => {
+                if projectile.hit_entities.contains(&hit_e) {
+                    // already pierced through
+                    continue;
+                }
 
-                        if shielded.is_none() {
-                            enemy_hp.0 -= 1;
-                        }
+                if let Ok((enemy, mut enemy_hp, mut impulse, shielded, garbage)) =
+                    enemy_q.get_mut(hit_e)
+                {
+                    if projectile.hit_entities.is_empty() && !projectile.parried {
+                        shot_hit_w.write(ShotHit);
+                    }
+                    projectile.hit_entities.push(hit_e);
 
-                        if enemy_hp.0 == 0 && shielded.is_none() {
-                            despawn_enemy_w.write(DespawnEnemy(hit_e));
-                        } else {
-                            // knockback
-                            impulse.0 += move_dir.0 * 30.;
+                    if shielded.is_none() {
+                        enemy_hp.0 = enemy_hp.0.saturating_sub(projectile.damage);
+                    }
+
+                    if enemy_hp.0 == 0 && shielded.is_none() {
+                        despawn_enemy_w.write(DespawnEnemy(hit_e));
+                        enemy_killed_w.write(EnemyKilled {
+                            kind: enemy.kind,
+                            by: if projectile.parried {
+                                KillSource::ReflectedProjectile
+                            } else {
+                                KillSource::Gun
+                            },
+                        });
+                        if !garbage {
+                            cmd.trigger(SendGarbage {
+                                from_core_e: enemy.core_e,
+                            });
                         }
+                    } else {
+                        // knockback
+                        impulse.0 += move_dir.0 * projectile.impulse;
                     }
-                }
-                ProjectileTarget::Core => {
-                    if core_q.contains(hit_e) {
+
+                    if shielded.is_some() || projectile.pierce == 0 {
                         despawn = true;
-                        taken_dmg_w.write_default();
-                    } else if paddle_q.contains(hit_e) {
-                        knockback_paddle_w.write(PaddleKnockback(-12.));
+                    } else {
+                        projectile.pierce -= 1;
+                    }
+                } else if barrier_q.contains(hit_e) {
+                    barrier_hit_w.write(BarrierHit(hit_e));
+                    despawn = true;
+                }
+            }
+            ProjectileTarget::Core => {
+                if core_q.contains(hit_e) {
+                    despawn = true;
+                    taken_dmg_w.write(TakeDamage(hit_e, DamageSource::EnemyProjectile));
+                } else if let Ok(parrying) = paddle_q.get(hit_e) {
+                    if parrying {
+                        cmd.trigger(ParryProjectile {
+                            projectile_e: e,
+                            paddle_e: hit_e,
+                        });
+                    } else {
+                        knockback_paddle_w.write(PaddleKnockback {
+                            paddle_e: hit_e,
+                            offset: -12.,
+                        });
                         despawn = true;
                     }
                 }
             }
+        }
 
-            if despawn {
-                projectile_hit_w.write(ProjectileDespawn(e));
-            }
+        if despawn {
+            projectile_hit_w.write(ProjectileDespawn(e));
+            despawned.push(e);
         }
     }
 }
@@ -0,0 +1,274 @@
+//! Hazards and pinball elements, spawned by the level script of the current arena.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_enoki::ParticleEffectHandle;
+use bevy_trauma_shake::Shakes;
+
+use crate::screen::in_game_state;
+
+use super::{
+    arena::{Arena, ArenaAssets, ArenaKind},
+    assets::ParticleAssets,
+    movement::Impulse,
+    pool::{PoolCommandsExt, PoolKey},
+    score::Score,
+    spawn::{
+        ball::Ball,
+        hazard::{
+            Barrier, BonusLane, DropTarget, DropTargetBank, GravityWell, Portal, Rotating,
+            SpawnHazard,
+        },
+        level::SpawnArena,
+        paddle::PaddleAmmo,
+        projectile::Projectile,
+    },
+    time::{Cooldown, process_cooldown},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelScript>()
+        .add_event::<PortalEntered>()
+        .add_event::<BarrierHit>()
+        .add_event::<DropTargetHit>()
+        .add_event::<BonusLaneEntered>()
+        .add_observer(start_level_script)
+        .add_systems(
+            Update,
+            (
+                run_level_script,
+                rotate_hazards,
+                pull_into_gravity_wells,
+                teleport_through_portals,
+                damage_barriers,
+                (drop_targets, raise_drop_targets).chain(),
+                score_bonus_lanes,
+                process_cooldown::<Portal>,
+            )
+                .run_if(in_game_state),
+        );
+}
+
+/// Time before the ball can go through a portal again, so it doesn't bounce between the pair.
+const PORTAL_COOLDOWN_S: f32 = 0.6;
+const DROP_TARGET_RESET_S: f32 = 2.;
+
+/// The ball hit a portal and should come out of its pair.
+#[derive(Event, Debug)]
+pub struct PortalEntered {
+    pub ball_e: Entity,
+    pub portal_e: Entity,
+}
+
+/// A barrier got hit by the ball or a player shot.
+#[derive(Event, Debug)]
+pub struct BarrierHit(pub Entity);
+
+#[derive(Event, Debug)]
+pub struct DropTargetHit(pub Entity);
+
+/// The ball rolled into a bonus lane.
+#[derive(Event, Debug)]
+pub struct BonusLaneEntered(pub Entity);
+
+/// Progress through the script of the current arena.
+#[derive(Resource, Debug, Default)]
+struct LevelScript {
+    arena: Option<ArenaKind>,
+    elapsed_s: f32,
+}
+
+fn start_level_script(trigger: Trigger<SpawnArena>, mut script: ResMut<LevelScript>) {
+    *script = LevelScript {
+        arena: Some(trigger.0),
+        elapsed_s: 0.,
+    };
+}
+
+fn run_level_script(
+    mut script: ResMut<LevelScript>,
+    arena_assets: Res<ArenaAssets>,
+    arenas: Res<Assets<Arena>>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let Some(arena) = script
+        .arena
+        .and_then(|kind| arenas.get(arena_assets.get(kind)))
+    else {
+        return;
+    };
+
+    let prev_s = script.elapsed_s;
+    script.elapsed_s += time.delta_secs();
+    for entry in &arena.script {
+        if entry.at_s >= prev_s && entry.at_s < script.elapsed_s {
+            cmd.trigger(SpawnHazard(entry.hazard.clone()));
+        }
+    }
+}
+
+fn rotate_hazards(mut rotating_q: Query<(&mut Transform, &Rotating)>, time: Res<Time>) {
+    for (mut t, rotating) in &mut rotating_q {
+        t.rotate_z(rotating.speed * time.delta_secs());
+    }
+}
+
+/// Bends the paths by nudging the impulse, stronger closer to the center.
+fn pull_into_gravity_wells(
+    well_q: Query<(&GlobalTransform, &GravityWell)>,
+    mut body_q: Query<(&GlobalTransform, &mut Impulse), Or<(With<Ball>, With<Projectile>)>>,
+    time: Res<Time>,
+) {
+    for (well_t, well) in &well_q {
+        let well_pos = well_t.translation().truncate();
+        for (body_t, mut impulse) in &mut body_q {
+            let to_well = well_pos - body_t.translation().truncate();
+            let dist = to_well.length();
+            if dist >= well.radius {
+                continue;
+            }
+            let falloff = 1. - dist / well.radius;
+            impulse.0 += to_well.normalize_or_zero() * well.strength * falloff * time.delta_secs();
+        }
+    }
+}
+
+fn teleport_through_portals(
+    mut entered_r: EventReader<PortalEntered>,
+    portal_q: Query<&Portal>,
+    mut ball_q: Query<&mut Transform, With<Ball>>,
+    mut cmd: Commands,
+) {
+    for ev in entered_r.read() {
+        let (Ok(portal), Ok(mut ball_t)) = (portal_q.get(ev.portal_e), ball_q.get_mut(ev.ball_e))
+        else {
+            continue;
+        };
+        ball_t.translation = portal.exit.extend(ball_t.translation.z);
+        cmd.entity(ev.ball_e)
+            .insert(Cooldown::<Portal>::new(PORTAL_COOLDOWN_S));
+    }
+}
+
+fn damage_barriers(
+    mut hit_r: EventReader<BarrierHit>,
+    mut barrier_q: Query<(&mut Barrier, &GlobalTransform)>,
+    particles: Res<ParticleAssets>,
+    mut shake: Shakes,
+    mut cmd: Commands,
+) {
+    for ev in hit_r.read() {
+        let Ok((mut barrier, t)) = barrier_q.get_mut(ev.0) else {
+            continue;
+        };
+        if barrier.health == 0 {
+            // already breaking from an earlier hit this frame
+            continue;
+        }
+        barrier.health -= 1;
+        if barrier.health > 0 {
+            continue;
+        }
+
+        cmd.entity(ev.0).despawn();
+        shake.add_trauma(0.25);
+        cmd.spawn_particles(
+            PoolKey::SquareParticles,
+            (
+                particles.square_particle_spawner(),
+                ParticleEffectHandle(particles.enemy.clone_weak()),
+                Transform::from_translation(t.translation().truncate().extend(10.)),
+            ),
+        );
+    }
+}
+
+/// Awards the same bonus to every paddle, co-op players share it.
+fn award_bonus(
+    score: &mut Score,
+    paddle_q: &mut Query<&mut PaddleAmmo>,
+    points: usize,
+    ammo: usize,
+) {
+    score.0 += points;
+    for mut paddle_ammo in paddle_q {
+        paddle_ammo.offset(ammo as isize);
+    }
+}
+
+fn drop_targets(
+    mut hit_r: EventReader<DropTargetHit>,
+    mut target_q: Query<(&mut DropTarget, &mut Visibility)>,
+    mut bank_q: Query<&mut DropTargetBank>,
+    mut paddle_q: Query<&mut PaddleAmmo>,
+    mut score: ResMut<Score>,
+    mut shake: Shakes,
+    mut cmd: Commands,
+) {
+    for ev in hit_r.read() {
+        let Ok((mut target, mut visibility)) = target_q.get_mut(ev.0) else {
+            continue;
+        };
+        if target.down {
+            continue;
+        }
+        target.down = true;
+        *visibility = Visibility::Hidden;
+        cmd.entity(ev.0).remove::<Collider>();
+
+        let bank_e = target.bank_e;
+        let all_down = target_q
+            .iter()
+            .filter(|(t, _)| t.bank_e == bank_e)
+            .all(|(t, _)| t.down);
+        let Ok(mut bank) = bank_q.get_mut(bank_e) else {
+            continue;
+        };
+        if all_down && bank.reset_timer.is_none() {
+            award_bonus(&mut score, &mut paddle_q, bank.points, bank.ammo);
+            bank.reset_timer = Some(Timer::from_seconds(DROP_TARGET_RESET_S, TimerMode::Once));
+            shake.add_trauma(0.3);
+        }
+    }
+}
+
+fn raise_drop_targets(
+    mut bank_q: Query<(Entity, &mut DropTargetBank)>,
+    mut target_q: Query<(Entity, &mut DropTarget, &mut Visibility)>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    for (bank_e, mut bank) in &mut bank_q {
+        let Some(timer) = bank.reset_timer.as_mut() else {
+            continue;
+        };
+        if !timer.tick(time.delta()).finished() {
+            continue;
+        }
+        bank.reset_timer = None;
+
+        for (target_e, mut target, mut visibility) in &mut target_q {
+            if target.bank_e != bank_e {
+                continue;
+            }
+            target.down = false;
+            *visibility = Visibility::Inherited;
+            cmd.entity(target_e)
+                .insert(Collider::rectangle(target.size.x, target.size.y));
+        }
+    }
+}
+
+fn score_bonus_lanes(
+    mut entered_r: EventReader<BonusLaneEntered>,
+    lane_q: Query<&BonusLane>,
+    mut paddle_q: Query<&mut PaddleAmmo>,
+    mut score: ResMut<Score>,
+) {
+    for ev in entered_r.read() {
+        if let Ok(lane) = lane_q.get(ev.0) {
+            award_bonus(&mut score, &mut paddle_q, lane.points, lane.ammo);
+        }
+    }
+}
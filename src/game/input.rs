@@ -9,6 +9,12 @@ use leafwing_input_manager::prelude::*;
 use crate::AppSet;
 use crate::math::asymptotic_smoothing_with_delta_time;
 
+use super::{
+    bot::Bot,
+    player::{Multiplayer, Player},
+    spawn::{level::Core, paddle::Paddle},
+};
+
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<CursorCoords>()
         .add_systems(
@@ -18,9 +24,9 @@ pub(super) fn plugin(app: &mut App) {
                 .after(InputManagerSystem::ManualControl),
         )
         .add_plugins(InputManagerPlugin::<PlayerAction>::default())
-        .init_resource::<AimDirection>()
+        // menu actions, players have their own input map & state
         .init_resource::<ActionState<PlayerAction>>()
-        .insert_resource(PlayerAction::input_map())
+        .insert_resource(PlayerAction::input_map(true, true))
         .init_state::<ActiveInput>()
         .add_systems(
             Update,
@@ -33,86 +39,231 @@ pub(super) fn plugin(app: &mut App) {
 pub enum PlayerAction {
     Shoot,
     TogglePaddleMode,
+    SwitchWeapon,
+    ChargeShot,
+    RepairGear,
+    CorePulse,
     #[actionlike(DualAxis)]
     AimGamepad,
     Quit,
     Restart,
 }
 
-#[derive(Resource, Debug, Default, Reflect)]
+#[derive(Component, Debug, Default, Reflect)]
 pub struct AimDirection(pub Vec2);
 
 impl PlayerAction {
-    fn input_map() -> InputMap<Self> {
+    pub fn input_map(mouse_keyboard: bool, gamepad: bool) -> InputMap<Self> {
         let mut input_map = InputMap::default();
+        if gamepad {
+            Self::insert_gamepad(&mut input_map);
+        }
+        if mouse_keyboard {
+            Self::insert_mouse_keyboard(&mut input_map);
+        }
+        input_map
+    }
 
-        // Gamepad
+    fn insert_gamepad(input_map: &mut InputMap<Self>) {
         let deadzone_radius = 0.15;
-        input_map.insert_dual_axis(
-            Self::AimGamepad,
-            GamepadStick::LEFT.with_circle_deadzone(deadzone_radius),
-        );
-        input_map.insert_dual_axis(
-            Self::AimGamepad,
-            GamepadStick::RIGHT.with_circle_deadzone(deadzone_radius),
-        );
-        input_map.insert(Self::Shoot, GamepadButton::RightTrigger);
-        input_map.insert(Self::Shoot, GamepadButton::RightTrigger2);
-        input_map.insert(Self::Shoot, GamepadButton::South);
-        input_map.insert(Self::TogglePaddleMode, GamepadButton::LeftTrigger);
-        input_map.insert(Self::TogglePaddleMode, GamepadButton::LeftTrigger2);
-        input_map.insert(Self::TogglePaddleMode, GamepadButton::West);
-        input_map.insert(Self::Restart, GamepadButton::Start);
-        input_map.insert(Self::Quit, GamepadButton::Select);
-
-        // KB & Mouse
-        input_map.insert(Self::Shoot, MouseButton::Left);
-        input_map.insert(Self::TogglePaddleMode, MouseButton::Right);
-        input_map.insert(Self::Quit, KeyCode::Escape);
-        input_map.insert(Self::Restart, KeyCode::KeyR);
+        for (stick, _) in GAMEPAD_STICKS {
+            input_map.insert_dual_axis(
+                Self::AimGamepad,
+                stick.with_circle_deadzone(deadzone_radius),
+            );
+        }
+        for (action, button, _) in GAMEPAD_BUTTONS {
+            input_map.insert(action, button);
+        }
+    }
 
-        input_map
+    fn insert_mouse_keyboard(input_map: &mut InputMap<Self>) {
+        for (action, button, _) in MOUSE_KEYBOARD_BUTTONS {
+            match button {
+                MouseKeyboardButton::Mouse(button) => input_map.insert(action, button),
+                MouseKeyboardButton::Key(key) => input_map.insert(action, key),
+            };
+        }
     }
+
+    /// Human readable bindings of the action for the given device, built from the input map tables.
+    pub fn binding_label(&self, input: ActiveInput) -> String {
+        let labels: Vec<&str> = match input {
+            // the paddle follows the cursor, it's not bound in the input map
+            ActiveInput::MouseKeyboard if *self == Self::AimGamepad => vec!["MOUSE"],
+            ActiveInput::MouseKeyboard => MOUSE_KEYBOARD_BUTTONS
+                .iter()
+                .filter(|(action, ..)| action == self)
+                .map(|(_, _, label)| *label)
+                .collect(),
+            ActiveInput::Gamepad if *self == Self::AimGamepad => {
+                GAMEPAD_STICKS.iter().map(|(_, label)| *label).collect()
+            }
+            ActiveInput::Gamepad => GAMEPAD_BUTTONS
+                .iter()
+                .filter(|(action, ..)| action == self)
+                .filter_map(|(_, _, label)| *label)
+                .collect(),
+        };
+        labels.join(" or ")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MouseKeyboardButton {
+    Mouse(MouseButton),
+    Key(KeyCode),
 }
 
+const MOUSE_KEYBOARD_BUTTONS: [(PlayerAction, MouseKeyboardButton, &str); 8] = [
+    (
+        PlayerAction::Shoot,
+        MouseKeyboardButton::Mouse(MouseButton::Left),
+        "LMB",
+    ),
+    (
+        PlayerAction::TogglePaddleMode,
+        MouseKeyboardButton::Mouse(MouseButton::Right),
+        "RMB",
+    ),
+    (
+        PlayerAction::SwitchWeapon,
+        MouseKeyboardButton::Key(KeyCode::KeyQ),
+        "Q",
+    ),
+    (
+        PlayerAction::ChargeShot,
+        MouseKeyboardButton::Key(KeyCode::Space),
+        "SPACE",
+    ),
+    (
+        PlayerAction::RepairGear,
+        MouseKeyboardButton::Key(KeyCode::KeyF),
+        "F",
+    ),
+    (
+        PlayerAction::CorePulse,
+        MouseKeyboardButton::Key(KeyCode::KeyE),
+        "E",
+    ),
+    (
+        PlayerAction::Quit,
+        MouseKeyboardButton::Key(KeyCode::Escape),
+        "ESC",
+    ),
+    (
+        PlayerAction::Restart,
+        MouseKeyboardButton::Key(KeyCode::KeyR),
+        "R",
+    ),
+];
+
+const GAMEPAD_STICKS: [(GamepadStick, &str); 2] = [
+    (GamepadStick::LEFT, "LEFT STICK"),
+    (GamepadStick::RIGHT, "RIGHT STICK"),
+];
+
+/// Bindings without a label are alternatives not worth mentioning in prompts.
+const GAMEPAD_BUTTONS: [(PlayerAction, GamepadButton, Option<&str>); 12] = [
+    (
+        PlayerAction::Shoot,
+        GamepadButton::RightTrigger,
+        Some("RIGHT TRIGGER"),
+    ),
+    (PlayerAction::Shoot, GamepadButton::RightTrigger2, None),
+    (PlayerAction::Shoot, GamepadButton::South, Some("A")),
+    (
+        PlayerAction::TogglePaddleMode,
+        GamepadButton::LeftTrigger,
+        Some("LEFT TRIGGER"),
+    ),
+    (
+        PlayerAction::TogglePaddleMode,
+        GamepadButton::LeftTrigger2,
+        None,
+    ),
+    (
+        PlayerAction::TogglePaddleMode,
+        GamepadButton::West,
+        Some("X"),
+    ),
+    (PlayerAction::SwitchWeapon, GamepadButton::North, Some("Y")),
+    (PlayerAction::ChargeShot, GamepadButton::East, Some("B")),
+    (
+        PlayerAction::RepairGear,
+        GamepadButton::DPadUp,
+        Some("DPAD UP"),
+    ),
+    (
+        PlayerAction::CorePulse,
+        GamepadButton::DPadDown,
+        Some("DPAD DOWN"),
+    ),
+    (PlayerAction::Restart, GamepadButton::Start, Some("START")),
+    (PlayerAction::Quit, GamepadButton::Select, Some("SELECT")),
+];
+
+/// Input device the player has used last.
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-enum ActiveInput {
+pub enum ActiveInput {
     #[default]
     MouseKeyboard,
     Gamepad,
 }
 
-pub type PlayerInput<'w> = Res<'w, ActionState<PlayerAction>>;
+pub type PlayerInput = ActionState<PlayerAction>;
 
 #[derive(Resource, Default)]
 struct CursorCoords(pub Vec2);
 
 fn update_aim_direction(
-    mut aim_dir: ResMut<AimDirection>,
+    mut aim_q: Query<(
+        &Player,
+        &Paddle,
+        &PlayerInput,
+        &InputMap<PlayerAction>,
+        &mut AimDirection,
+        Has<Bot>,
+    )>,
+    core_q: Query<&GlobalTransform, With<Core>>,
     input_state: Res<State<ActiveInput>>,
     cursor: Res<CursorCoords>,
-    input: PlayerInput,
+    multiplayer: Res<Multiplayer>,
     time: Res<Time>,
 ) {
-    aim_dir.0 = match input_state.get() {
-        ActiveInput::MouseKeyboard => {
+    for (player, paddle, input, map, mut aim_dir, bot) in &mut aim_q {
+        // only the first player can use the mouse, bots aim with the stick axis
+        let mouse = !bot
+            && player.0 == 0
+            && match *multiplayer {
+                Multiplayer::Off => *input_state.get() == ActiveInput::MouseKeyboard,
+                _ => map.gamepad().is_none(),
+            };
+        aim_dir.0 = if mouse {
+            // aim relative to the player's own core
+            let core_pos = core_q
+                .get(paddle.core_e)
+                .map(|t| t.translation().truncate())
+                .unwrap_or_default();
+            let cursor = cursor.0 - core_pos;
             let deadzone_radius = 70.0;
-            let dist = cursor.0.length();
+            let dist = cursor.length();
             if dist >= deadzone_radius {
-                cursor.0.normalize_or(aim_dir.0)
+                cursor.normalize_or(aim_dir.0)
             } else {
                 asymptotic_smoothing_with_delta_time(
                     aim_dir.0,
-                    cursor.0,
+                    cursor,
                     (dist / deadzone_radius).powi(3),
                     time.delta_secs(),
                 )
                 // aim_dir.0
             }
-        }
-        ActiveInput::Gamepad => input
-            .clamped_axis_pair(&PlayerAction::AimGamepad)
-            .normalize_or(aim_dir.0),
+        } else {
+            input
+                .clamped_axis_pair(&PlayerAction::AimGamepad)
+                .normalize_or(aim_dir.0)
+        };
     }
 }
 
@@ -122,7 +273,10 @@ fn update_cursor_coords(
     q_camera: Query<(&Camera, &GlobalTransform), With<IsDefaultUiCamera>>,
 ) {
     let (camera, camera_transform) = q_camera.single().expect("Camera exists");
-    let window = q_window.single().expect("Window exists");
+    // headless builds have no window
+    let Ok(window) = q_window.single() else {
+        return;
+    };
 
     // check the cursor is inside the window and get its position
     // then convert into world coordinates
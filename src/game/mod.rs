@@ -2,18 +2,38 @@
 
 use bevy::prelude::*;
 
+pub mod achievement;
+pub mod arena;
 pub mod assets;
+mod attract;
 pub mod audio;
 pub mod ball;
-mod core;
+pub mod bot;
+pub mod collision;
+pub mod core;
+pub mod daily;
+pub mod gesture;
 mod gun;
+mod hazard;
 pub mod input;
+pub mod mode;
 mod movement;
 pub mod paddle;
+mod parry;
+mod pickup;
+pub mod pool;
+pub mod player;
+pub mod progression;
+pub mod rng;
+mod sandbox;
 pub mod score;
 pub mod spawn;
+pub mod stats;
 pub mod time;
+pub mod tutorial;
 pub mod tween;
+pub mod versus;
+pub mod weapon;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
@@ -29,5 +49,29 @@ pub(super) fn plugin(app: &mut App) {
         ball::plugin,
         paddle::plugin,
         score::plugin,
+    ))
+    .add_plugins((
+        gesture::plugin,
+        parry::plugin,
+        pickup::plugin,
+        weapon::plugin,
+        player::plugin,
+        versus::plugin,
+        daily::plugin,
+        mode::plugin,
+        rng::plugin,
+        sandbox::plugin,
+        tutorial::plugin,
+        bot::plugin,
+        attract::plugin,
+        stats::plugin,
+        achievement::plugin,
+    ))
+    .add_plugins((
+        progression::plugin,
+        arena::plugin,
+        hazard::plugin,
+        collision::plugin,
+        pool::plugin,
     ));
 }
@@ -0,0 +1,272 @@
+//! Game modes and the rules they set for a run.
+
+use bevy::prelude::*;
+
+use crate::screen::{NextTransitionedState, Screen, in_game_state};
+
+use super::{
+    arena::{ArenaKind, ArenaSelection},
+    attract::AttractRun,
+    bot::BotPlayers,
+    daily::DailyRun,
+    player::Multiplayer,
+    progression::Progression,
+    spawn::{
+        enemy::{EnemyKind, EnemySet},
+        level::GEAR_COUNT,
+    },
+    tutorial::TutorialRun,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GameMode>()
+        .init_resource::<RunRules>()
+        .init_resource::<ModeClock>()
+        .add_systems(
+            OnEnter(Screen::Game),
+            (apply_rules, spawn_clock_hud).chain(),
+        )
+        .add_systems(
+            Update,
+            (tick_mode_clock, update_clock_hud)
+                .chain()
+                .run_if(in_game_state),
+        );
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    TimeAttack,
+    Zen,
+    Hardcore,
+    BallOnly,
+    Sandbox,
+    /// Only started from the tutorial button, so it isn't part of [`GameMode::ALL`].
+    Tutorial,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 6] = [
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Zen,
+        GameMode::Hardcore,
+        GameMode::BallOnly,
+        GameMode::Sandbox,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "ENDLESS",
+            GameMode::TimeAttack => "TIME ATTACK",
+            GameMode::Zen => "ZEN",
+            GameMode::Hardcore => "HARDCORE",
+            GameMode::BallOnly => "BALL ONLY",
+            GameMode::Sandbox => "SANDBOX",
+            GameMode::Tutorial => "TUTORIAL",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|m| m == self).unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn def(&self) -> GameModeDef {
+        match self {
+            GameMode::Endless => GameModeDef::default(),
+            GameMode::TimeAttack => GameModeDef {
+                time_limit_s: Some(180.),
+                arena: ArenaSelection::Rotating(&ArenaKind::ALL),
+                ..default()
+            },
+            GameMode::Zen => GameModeDef {
+                core_damage: false,
+                arena: ArenaSelection::Fixed(ArenaKind::Circle),
+                ..default()
+            },
+            GameMode::Hardcore => GameModeDef {
+                gears: 1,
+                spawn_interval_mult: 0.6,
+                arena: ArenaSelection::Fixed(ArenaKind::Octagon),
+                ..default()
+            },
+            GameMode::BallOnly => GameModeDef {
+                gun_enabled: false,
+                arena: ArenaSelection::Fixed(ArenaKind::Pillars),
+                ..default()
+            },
+            GameMode::Sandbox => GameModeDef {
+                core_damage: false,
+                spawner_enabled: false,
+                sandbox_tools: true,
+                arena: ArenaSelection::Fixed(ArenaKind::Bumpers),
+                ..default()
+            },
+            GameMode::Tutorial => GameModeDef {
+                core_damage: false,
+                spawner_enabled: false,
+                loadout: false,
+                ..default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GameModeDef {
+    /// The run ends once the time runs out instead of on core destruction.
+    pub time_limit_s: Option<f32>,
+    pub core_damage: bool,
+    /// Number of gears, i.e. hits, the core starts with.
+    pub gears: u8,
+    pub spawner_enabled: bool,
+    /// Multiplier of the time between enemy spawns.
+    pub spawn_interval_mult: f32,
+    pub gun_enabled: bool,
+    pub ball_speed_mult: f32,
+    /// Every spawned enemy is of this kind.
+    pub enemy_kind: Option<EnemyKind>,
+    pub enemy_set: EnemySet,
+    /// Show the sandbox panel for spawning enemies and tweaking the run.
+    pub sandbox_tools: bool,
+    /// The starting options unlocked with scrap are applied.
+    pub loadout: bool,
+    pub arena: ArenaSelection,
+}
+
+impl Default for GameModeDef {
+    fn default() -> Self {
+        Self {
+            time_limit_s: None,
+            core_damage: true,
+            gears: GEAR_COUNT,
+            spawner_enabled: true,
+            spawn_interval_mult: 1.,
+            gun_enabled: true,
+            ball_speed_mult: 1.,
+            enemy_kind: None,
+            enemy_set: EnemySet::Standard,
+            sandbox_tools: false,
+            loadout: true,
+            arena: ArenaSelection::Fixed(ArenaKind::Square),
+        }
+    }
+}
+
+/// Rules of the current run, the [`GameMode`] definition with the daily modifier applied.
+#[derive(Resource, Debug, Default, Deref)]
+pub struct RunRules(pub GameModeDef);
+
+pub fn gun_enabled(rules: Res<RunRules>) -> bool {
+    rules.gun_enabled
+}
+
+pub fn spawner_enabled(rules: Res<RunRules>) -> bool {
+    rules.spawner_enabled
+}
+
+pub fn sandbox_tools(rules: Res<RunRules>) -> bool {
+    rules.sandbox_tools
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ModeClock {
+    pub elapsed_s: f32,
+    /// The time limit has been reached.
+    pub time_up: bool,
+}
+
+#[derive(Component, Debug)]
+struct ClockHud;
+
+/// Other systems on entering the game that read the [`RunRules`] have to run after this.
+pub fn apply_rules(
+    mode: Res<GameMode>,
+    attract: Res<AttractRun>,
+    daily: Res<DailyRun>,
+    tutorial: Res<TutorialRun>,
+    multiplayer: Res<Multiplayer>,
+    bots: Res<BotPlayers>,
+    progression: Res<Progression>,
+    mut rules: ResMut<RunRules>,
+    mut clock: ResMut<ModeClock>,
+) {
+    // daily challenges and versus matches always play by the endless rules
+    // the demo shouldn't end in a game over
+    let mut def = if attract.0 {
+        GameMode::Zen.def()
+    } else if tutorial.0 {
+        GameMode::Tutorial.def()
+    } else if daily.0.is_some() || *multiplayer == Multiplayer::Versus {
+        GameMode::Endless.def()
+    } else {
+        mode.def()
+    };
+    if let Some(modifier) = daily.modifier() {
+        modifier.apply(&mut def);
+    }
+    // keep shared and simulated runs comparable
+    if attract.0 || daily.0.is_some() || *multiplayer == Multiplayer::Versus || bots.0.is_some() {
+        def.loadout = false;
+    }
+    if def.loadout
+        && let Some(set) = progression.enemy_set()
+    {
+        def.enemy_set = set;
+    }
+    rules.0 = def;
+    *clock = ModeClock::default();
+}
+
+fn spawn_clock_hud(mut cmd: Commands, rules: Res<RunRules>) {
+    if rules.time_limit_s.is_none() {
+        return;
+    }
+
+    cmd.spawn((
+        Name::new("clock_hud"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.),
+            width: Val::Percent(100.),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Game),
+        children![(ClockHud, Text::default(), TextFont::from_font_size(32.),)],
+    ));
+}
+
+fn tick_mode_clock(
+    mut clock: ResMut<ModeClock>,
+    rules: Res<RunRules>,
+    time: Res<Time>,
+    mut next: ResMut<NextTransitionedState>,
+) {
+    clock.elapsed_s += time.delta_secs();
+    if let Some(limit) = rules.time_limit_s
+        && clock.elapsed_s >= limit
+        && !clock.time_up
+    {
+        clock.time_up = true;
+        next.set(Screen::GameOver);
+    }
+}
+
+fn update_clock_hud(
+    mut hud_q: Query<&mut Text, With<ClockHud>>,
+    clock: Res<ModeClock>,
+    rules: Res<RunRules>,
+) {
+    let Some(limit) = rules.time_limit_s else {
+        return;
+    };
+    let remaining = (limit - clock.elapsed_s).max(0.).ceil() as u32;
+    for mut text in &mut hud_q {
+        text.0 = format!("{}:{:02}", remaining / 60, remaining % 60);
+    }
+}
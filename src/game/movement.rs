@@ -1,11 +1,10 @@
 use std::ops::Range;
 
+use avian2d::prelude::*;
 use bevy::prelude::*;
 
 use crate::{GAME_SIZE, ext::QuatExt};
 
-use super::time::{Cooldown, process_cooldown};
-
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<MoveDirection>()
         .register_type::<Damping>()
@@ -15,7 +14,6 @@ pub(super) fn plugin(app: &mut App) {
         .add_systems(
             Update,
             (
-                process_cooldown::<MovementPaused>,
                 (
                     apply_damping,
                     compute_velocity.after(apply_damping),
@@ -23,7 +21,7 @@ pub(super) fn plugin(app: &mut App) {
                     home.after(apply_impulse),
                 )
                     .before(ApplyVelocitySet),
-                apply_velocity.in_set(ApplyVelocitySet),
+                (apply_velocity, apply_body_velocity).in_set(ApplyVelocitySet),
                 (accumulate_angle, follow).after(ApplyVelocitySet),
             ),
         );
@@ -66,12 +64,6 @@ pub struct Impulse(pub Vec2);
 #[derive(Component, Debug, Reflect)]
 pub struct MovementPaused;
 
-impl MovementPaused {
-    pub fn cooldown(duration_s: f32) -> Cooldown<MovementPaused> {
-        Cooldown::new(duration_s)
-    }
-}
-
 impl Speed {
     pub fn speed_factor(&self, min: f32, max: f32) -> f32 {
         speed_factor(self.0, min, max)
@@ -120,7 +112,7 @@ fn compute_velocity(
             Option<&SpeedMultiplier>,
             &mut Velocity,
         ),
-        (Without<MovementPaused>, Without<Cooldown<MovementPaused>>),
+        Without<MovementPaused>,
     >,
     time: Res<Time>,
 ) {
@@ -130,10 +122,7 @@ fn compute_velocity(
 }
 
 fn apply_impulse(
-    mut impulse_q: Query<
-        (&mut Impulse, &mut Velocity),
-        (Without<MovementPaused>, Without<Cooldown<MovementPaused>>),
-    >,
+    mut impulse_q: Query<(&mut Impulse, &mut Velocity), Without<MovementPaused>>,
     time: Res<Time>,
 ) {
     for (mut impulse, mut vel) in &mut impulse_q {
@@ -145,20 +134,31 @@ fn apply_impulse(
 }
 
 fn apply_velocity(
-    mut move_q: Query<
-        (&mut Transform, &Velocity),
-        (Without<MovementPaused>, Without<Cooldown<MovementPaused>>),
-    >,
+    mut move_q: Query<(&mut Transform, &Velocity), (Without<MovementPaused>, Without<RigidBody>)>,
 ) {
     for (mut t, vel) in &mut move_q {
         t.translation += vel.0.extend(0.);
     }
 }
 
+/// Rigid bodies are moved by the physics step instead, so their sweeps can catch fast hits.
+fn apply_body_velocity(
+    mut body_q: Query<(&Velocity, &mut LinearVelocity, Has<MovementPaused>)>,
+    time: Res<Time>,
+) {
+    for (vel, mut linear_vel, paused) in &mut body_q {
+        linear_vel.0 = if paused || time.delta_secs() <= 0. {
+            Vec2::ZERO
+        } else {
+            vel.0 / time.delta_secs()
+        };
+    }
+}
+
 fn home(
     mut move_q: Query<
         (&Transform, &mut Velocity, &MoveDirection, &Homing, &Speed),
-        (Without<MovementPaused>, Without<Cooldown<MovementPaused>>),
+        Without<MovementPaused>,
     >,
     time: Res<Time>,
     target_q: Query<&GlobalTransform, With<HomingTarget>>,
@@ -219,10 +219,7 @@ fn home(
 }
 
 fn apply_damping(
-    mut damping_q: Query<
-        (&mut Velocity, &Damping, Option<&mut Speed>),
-        (Without<MovementPaused>, Without<Cooldown<MovementPaused>>),
-    >,
+    mut damping_q: Query<(&mut Velocity, &Damping, Option<&mut Speed>), Without<MovementPaused>>,
     time: Res<Time>,
 ) {
     for (mut vel, damping, speed) in &mut damping_q {
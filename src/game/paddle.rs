@@ -5,18 +5,23 @@ use std::f32::consts::TAU;
 use crate::{
     AppSet,
     event::send_delayed_event,
-    ext::{EventReaderExt, QuatExt, Vec2Ext},
+    ext::{QuatExt, Vec2Ext},
     screen::Screen,
+    theme::palette::COL_PADDLE_PARRY,
 };
 
 use super::{
     ball::MaxBallSpeedFactor,
+    gesture::{Gesture, GesturePerformed},
     input::{AimDirection, PlayerAction, PlayerInput},
-    movement::{AccumulatedRotation, MoveDirection, MovementPaused},
+    movement::{MoveDirection, MovementPaused},
+    parry::{ParryWindow, parry_threat},
+    player::Player,
     spawn::{
         ball::{Ball, SpawnBall},
         level::AmmoUi,
         paddle::{Paddle, PaddleAmmo, PaddleMode, PaddleRotation},
+        projectile::Projectile,
     },
     time::{Cooldown, process_cooldown},
     tween::{
@@ -30,7 +35,7 @@ pub(super) fn plugin(app: &mut App) {
         (
             process_input.in_set(AppSet::ProcessInput),
             rotate_paddle,
-            apply_cycle_effects,
+            apply_gestures,
             knockback_paddle,
             process_cooldown::<PaddleMode>,
             send_delayed_event::<PaddleKnockback>,
@@ -42,57 +47,87 @@ pub(super) fn plugin(app: &mut App) {
 pub const PADDLE_REVOLUTION_DURATION_MIN: f32 = 0.45;
 
 #[derive(Event, Debug)]
-pub struct PaddleKnockback(pub f32);
+pub struct PaddleKnockback {
+    pub paddle_e: Entity,
+    pub offset: f32,
+}
 
 fn process_input(
-    input: PlayerInput,
     mut paddle_mode_q: Query<
-        (Entity, &Paddle, &mut PaddleMode, &GlobalTransform),
+        (
+            Entity,
+            &Paddle,
+            &PlayerInput,
+            &mut PaddleMode,
+            &GlobalTransform,
+            Has<ParryWindow>,
+        ),
         Without<Cooldown<PaddleMode>>,
     >,
+    projectile_q: Query<(&Projectile, &GlobalTransform)>,
     mut cmd: Commands,
-    mut ball_q: Query<&mut MoveDirection, With<Ball>>,
+    mut ball_q: Query<(&mut Ball, &mut MoveDirection)>,
 ) {
     // todo: cooldown?
-    if input.just_pressed(&PlayerAction::TogglePaddleMode) {
-        for (e, paddle, mut pm, paddle_t) in &mut paddle_mode_q {
-            *pm = match *pm {
-                PaddleMode::Reflect => PaddleMode::Capture,
-                PaddleMode::Capture => PaddleMode::Reflect,
-                PaddleMode::Captured {
-                    shoot_rotation,
-                    ball_e,
-                } => {
-                    if let Ok(mut move_dir) = ball_q.get_mut(ball_e) {
-                        let dir = (Quat::from_rotation_z(shoot_rotation.as_radians())
-                            * -paddle_t.right())
-                        .truncate()
-                        .normalize_or_zero();
-                        move_dir.0 = dir;
-                        cmd.entity(ball_e)
-                            .remove_parent_in_place()
-                            .remove::<MovementPaused>();
-                    }
-                    PaddleMode::Reflect
-                }
-            };
-            cmd.entity(paddle.reflect_e)
-                .try_insert(get_relative_sprite_color_anim(
-                    pm.color(),
-                    150,
-                    Some(EaseFunction::QuadraticOut),
-                ));
-            cmd.entity(e).try_insert(Cooldown::<PaddleMode>::new(0.15));
+    for (e, paddle, input, mut pm, paddle_t, parrying) in &mut paddle_mode_q {
+        if !input.just_pressed(&PlayerAction::TogglePaddleMode) {
+            continue;
+        }
+
+        if !matches!(*pm, PaddleMode::Captured { .. }) && parry_threat(paddle_t, &projectile_q) {
+            // parry instead of toggling the mode when an enemy projectile is closing in
+            if !parrying {
+                cmd.entity(e).try_insert(ParryWindow::default());
+                cmd.entity(paddle.reflect_e)
+                    .try_insert(get_relative_sprite_color_anim(
+                        COL_PADDLE_PARRY,
+                        60,
+                        Some(EaseFunction::QuadraticOut),
+                    ));
+            }
+            continue;
         }
+
+        *pm = match *pm {
+            PaddleMode::Reflect => PaddleMode::Capture,
+            PaddleMode::Capture => PaddleMode::Reflect,
+            PaddleMode::Captured {
+                shoot_rotation,
+                ball_e,
+            } => {
+                if let Ok((mut ball, mut move_dir)) = ball_q.get_mut(ball_e) {
+                    let dir = (Quat::from_rotation_z(shoot_rotation.as_radians())
+                        * -paddle_t.right())
+                    .truncate()
+                    .normalize_or_zero();
+                    move_dir.0 = dir;
+                    ball.launched_from = Some(paddle.core_e);
+                    cmd.entity(ball_e)
+                        .remove_parent_in_place()
+                        .remove::<MovementPaused>();
+                }
+                PaddleMode::Reflect
+            }
+        };
+        cmd.entity(paddle.reflect_e)
+            .try_insert(get_relative_sprite_color_anim(
+                pm.color(),
+                150,
+                Some(EaseFunction::QuadraticOut),
+            ));
+        cmd.entity(e).try_insert(Cooldown::<PaddleMode>::new(0.15));
     }
 }
 
 fn rotate_paddle(
-    mut rot_q: Query<&mut Transform, With<PaddleRotation>>,
-    aim_dir: Res<AimDirection>,
+    mut rot_q: Query<(&mut Transform, &PaddleRotation)>,
+    aim_q: Query<&AimDirection>,
     time: Res<Time<Real>>,
 ) {
-    for mut t in rot_q.iter_mut() {
+    for (mut t, paddle_rot) in rot_q.iter_mut() {
+        let Ok(aim_dir) = aim_q.get(paddle_rot.paddle_e) else {
+            continue;
+        };
         let current_angle = t.rotation.to_rot2();
         let target_angle = aim_dir.0.to_rot2();
         let max_delta = (time.delta_secs() / PADDLE_REVOLUTION_DURATION_MIN) * TAU;
@@ -103,60 +138,48 @@ fn rotate_paddle(
     }
 }
 
-fn apply_cycle_effects(
-    mut rot_q: Query<(&mut PaddleRotation, &AccumulatedRotation)>,
+fn apply_gestures(
+    mut gesture_r: EventReader<GesturePerformed>,
+    rot_q: Query<&PaddleRotation>,
     mut ammo_q: Query<&mut PaddleAmmo>,
-    ammo_ui_q: Query<Entity, With<AmmoUi>>,
+    player_q: Query<&Player>,
+    ammo_ui_q: Query<(Entity, &Player), With<AmmoUi>>,
     ball_speed_factor: Res<MaxBallSpeedFactor>,
     mut cmd: Commands,
-    time: Res<Time>,
 ) {
-    for (mut paddle_rot, angle) in rot_q.iter_mut() {
-        if (angle.rotation - paddle_rot.cw_start) <= -720f32.to_radians() {
-            // CW (negative angle)
-            paddle_rot.reset(angle.rotation);
-            cmd.trigger(SpawnBall {
-                paddle_e: paddle_rot.paddle_e,
-                tween_delay_ms: 0,
-            });
-        } else if (angle.rotation - paddle_rot.ccw_start) >= 360f32.to_radians() {
-            // CCW (positive angle)
-            for mut ammo in &mut ammo_q {
-                ammo.offset(ball_speed_factor.ammo_bonus() as isize);
+    for ev in gesture_r.read() {
+        let Ok(paddle_rot) = rot_q.get(ev.entity) else {
+            continue;
+        };
+        match ev.gesture {
+            Gesture::RecallBall => {
+                cmd.trigger(SpawnBall {
+                    paddle_e: paddle_rot.paddle_e,
+                    tween_delay_ms: 0,
+                });
             }
-            for e in &ammo_ui_q {
-                cmd.entity(e).try_insert(Animator::new(
-                    get_relative_scale_tween(
-                        Vec2::splat(1.25).extend(1.),
-                        400,
-                        Some(EaseFunction::BackOut),
-                    )
-                    .then(get_relative_scale_tween(
-                        Vec3::ONE,
-                        200,
-                        Some(EaseFunction::QuadraticOut),
-                    )),
-                ));
-            }
-            paddle_rot.reset(angle.rotation);
-        } else if angle.rotation > paddle_rot.cw_start {
-            paddle_rot.cw_start = angle.rotation;
-        } else if angle.rotation < paddle_rot.ccw_start {
-            paddle_rot.ccw_start = angle.rotation;
-        }
-
-        let delta = (paddle_rot.prev_rot - angle.rotation).abs() / time.delta_secs();
-        if delta < 3. {
-            // reset if rotation doesn't change for a while
-            paddle_rot.timer.tick(time.delta());
-            if paddle_rot.timer.just_finished() {
-                paddle_rot.reset(angle.rotation);
+            Gesture::GainAmmo => {
+                if let Ok(mut ammo) = ammo_q.get_mut(paddle_rot.paddle_e) {
+                    ammo.offset(ball_speed_factor.ammo_bonus() as isize);
+                }
+                let player = player_q.get(paddle_rot.paddle_e).ok();
+                for (e, _) in ammo_ui_q.iter().filter(|(_, p)| Some(*p) == player) {
+                    cmd.entity(e).try_insert(Animator::new(
+                        get_relative_scale_tween(
+                            Vec2::splat(1.25).extend(1.),
+                            400,
+                            Some(EaseFunction::BackOut),
+                        )
+                        .then(get_relative_scale_tween(
+                            Vec3::ONE,
+                            200,
+                            Some(EaseFunction::QuadraticOut),
+                        )),
+                    ));
+                }
             }
-        } else {
-            paddle_rot.timer.reset()
+            _ => {}
         }
-
-        paddle_rot.prev_rot = angle.rotation;
     }
 }
 
@@ -164,23 +187,23 @@ fn apply_cycle_effects(
 fn knockback_paddle(
     mut ev_r: EventReader<PaddleKnockback>,
     mut cmd: Commands,
-    paddle_q: Query<(&Paddle, &Transform), With<Paddle>>,
-) -> Result {
-    let Some(ev) = ev_r.read_only_last() else {
-        return Ok(());
-    };
-    let (paddle, t) = paddle_q.single()?;
-    cmd.entity(paddle.sprite_e).insert(Animator::new(
-        get_relative_translation_tween(
-            (Vec2::X * ev.0).extend(t.translation.z),
-            60,
-            Some(EaseFunction::QuadraticOut),
-        )
-        .then(get_relative_translation_tween(
-            Vec3::ZERO,
-            110,
-            Some(EaseFunction::BackOut),
-        )),
-    ));
-    Ok(())
+    paddle_q: Query<(&Paddle, &Transform)>,
+) {
+    for ev in ev_r.read() {
+        let Ok((paddle, t)) = paddle_q.get(ev.paddle_e) else {
+            continue;
+        };
+        cmd.entity(paddle.sprite_e).insert(Animator::new(
+            get_relative_translation_tween(
+                (Vec2::X * ev.offset).extend(t.translation.z),
+                60,
+                Some(EaseFunction::QuadraticOut),
+            )
+            .then(get_relative_translation_tween(
+                Vec3::ZERO,
+                110,
+                Some(EaseFunction::BackOut),
+            )),
+        ));
+    }
 }
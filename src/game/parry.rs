@@ -0,0 +1,250 @@
+//! Timed parry of enemy projectiles.
+//!
+//! Pressing [`PlayerAction::TogglePaddleMode`](super::input::PlayerAction) while an enemy projectile
+//! is closing in on the paddle opens a short parry window instead of toggling the paddle mode.
+//! A projectile hitting the paddle during the window is reflected back at the enemies,
+//! while a window that runs out without a hit puts the paddle mode on a longer cooldown.
+
+use bevy::prelude::*;
+use bevy_enoki::ParticleEffectHandle;
+use bevy_trauma_shake::Shakes;
+use bevy_tweening::Animator;
+
+use crate::{
+    ext::Vec2Ext,
+    math::asymptotic_smoothing_with_delta_time,
+    screen::in_game_state,
+    theme::palette::{COL_BULLET, COL_PADDLE_PARRY_MISS},
+};
+
+use super::{
+    assets::ParticleAssets,
+    movement::{Damping, MoveDirection, Speed},
+    player::Player,
+    pool::{PoolCommandsExt, PoolKey},
+    spawn::{
+        level::{AmmoUi, Core},
+        paddle::{Paddle, PaddleAmmo, PaddleMode},
+        projectile::{Projectile, ProjectileTarget},
+    },
+    time::{Cooldown, DilateTime},
+    tween::{
+        delay_tween, get_relative_scale_anim, get_relative_scale_tween,
+        get_relative_sprite_color_anim, get_relative_sprite_color_tween,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BloomFlash>()
+        .add_observer(parry_projectile)
+        .add_systems(
+            Update,
+            (telegraph_parry, expire_parry_window, fade_bloom_flash).run_if(in_game_state),
+        );
+}
+
+/// How long the parry window stays open after pressing the toggle.
+pub const PARRY_WINDOW_S: f32 = 0.2;
+/// Distance from the paddle at which an enemy projectile can be parried.
+pub const PARRY_THREAT_DISTANCE: f32 = 260.;
+/// Paddle mode cooldown applied after a mistimed parry.
+pub const PARRY_MISS_COOLDOWN_S: f32 = 0.9;
+pub const PARRY_AMMO_REWARD: isize = 4;
+pub const PARRY_PROJECTILE_SPEED: f32 = 1400.;
+pub const PARRY_HITSTOP_MS: u64 = 80;
+pub const PARRY_BLOOM_FLASH: f32 = 0.35;
+
+#[derive(Event, Debug)]
+pub struct ParryProjectile {
+    pub projectile_e: Entity,
+    pub paddle_e: Entity,
+}
+
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct ParryWindow(Timer);
+
+impl Default for ParryWindow {
+    fn default() -> Self {
+        Self(Timer::from_seconds(PARRY_WINDOW_S, TimerMode::Once))
+    }
+}
+
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+struct ParryTelegraph;
+
+/// Extra bloom intensity that fades out over time.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct BloomFlash(pub f32);
+
+pub fn parry_threat(
+    paddle_t: &GlobalTransform,
+    projectile_q: &Query<(&Projectile, &GlobalTransform)>,
+) -> bool {
+    projectile_q.iter().any(|(projectile, t)| {
+        projectile.target == ProjectileTarget::Core
+            && t.translation().distance(paddle_t.translation()) < PARRY_THREAT_DISTANCE
+    })
+}
+
+fn telegraph_parry(
+    paddle_q: Query<(Entity, &Paddle, &GlobalTransform, Has<ParryTelegraph>)>,
+    projectile_q: Query<(&Projectile, &GlobalTransform)>,
+    mut cmd: Commands,
+) {
+    for (e, paddle, paddle_t, telegraphed) in &paddle_q {
+        let threat = parry_threat(paddle_t, &projectile_q);
+        if threat && !telegraphed {
+            cmd.entity(e).try_insert(ParryTelegraph);
+            cmd.entity(paddle.reflect_e)
+                .try_insert(get_relative_scale_anim(
+                    Vec2::splat(1.25).extend(1.),
+                    120,
+                    Some(EaseFunction::BackOut),
+                ));
+        } else if !threat && telegraphed {
+            cmd.entity(e).remove::<ParryTelegraph>();
+            cmd.entity(paddle.reflect_e)
+                .try_insert(get_relative_scale_anim(
+                    Vec3::ONE,
+                    150,
+                    Some(EaseFunction::QuadraticOut),
+                ));
+        }
+    }
+}
+
+fn expire_parry_window(
+    mut window_q: Query<(Entity, &Paddle, &PaddleMode, &mut ParryWindow)>,
+    mut cmd: Commands,
+    time: Res<Time>,
+) {
+    for (e, paddle, paddle_mode, mut window) in &mut window_q {
+        window.0.tick(time.delta());
+        if window.0.just_finished() {
+            // mistimed parry
+            cmd.entity(e)
+                .remove::<ParryWindow>()
+                .try_insert(Cooldown::<PaddleMode>::new(PARRY_MISS_COOLDOWN_S));
+            cmd.entity(paddle.reflect_e).try_insert(Animator::new(
+                get_relative_sprite_color_tween(
+                    COL_PADDLE_PARRY_MISS,
+                    80,
+                    Some(EaseFunction::QuadraticOut),
+                )
+                .then(delay_tween(
+                    get_relative_sprite_color_tween(
+                        paddle_mode.color(),
+                        150,
+                        Some(EaseFunction::QuadraticIn),
+                    ),
+                    (PARRY_MISS_COOLDOWN_S * 1000.) as u64 - 230,
+                )),
+            ));
+        }
+    }
+}
+
+fn parry_projectile(
+    trigger: Trigger<ParryProjectile>,
+    mut projectile_q: Query<(
+        &mut Projectile,
+        &mut MoveDirection,
+        &mut Speed,
+        &mut Damping,
+        &GlobalTransform,
+    )>,
+    mut paddle_q: Query<(&Paddle, &PaddleMode, &mut PaddleAmmo, &Player)>,
+    core_q: Query<&GlobalTransform, With<Core>>,
+    ammo_ui_q: Query<(Entity, &Player), With<AmmoUi>>,
+    mut cmd: Commands,
+    mut shake: Shakes,
+    mut bloom_flash: ResMut<BloomFlash>,
+    mut dilate_time_w: EventWriter<DilateTime>,
+    particles: Res<ParticleAssets>,
+) {
+    let ev = trigger.event();
+    let (
+        Ok((mut projectile, mut move_dir, mut speed, mut damping, projectile_t)),
+        Ok((paddle, paddle_mode, mut ammo, player)),
+    ) = (
+        projectile_q.get_mut(ev.projectile_e),
+        paddle_q.get_mut(ev.paddle_e),
+    )
+    else {
+        return;
+    };
+
+    // send the projectile back where it came from
+    let core_pos = core_q
+        .get(paddle.core_e)
+        .map(|t| t.translation())
+        .unwrap_or_default();
+    projectile.target = ProjectileTarget::Enemy;
+    projectile.parried = true;
+    cmd.entity(ev.projectile_e)
+        .try_insert(projectile.target.layer().layers());
+    move_dir.0 = (projectile_t.translation() - core_pos)
+        .truncate()
+        .normalize_or(-move_dir.0);
+    speed.0 = PARRY_PROJECTILE_SPEED;
+    damping.0 = 0.8;
+    cmd.entity(projectile.sprite_e)
+        .try_insert(get_relative_sprite_color_anim(
+            COL_BULLET,
+            100,
+            Some(EaseFunction::QuadraticOut),
+        ));
+
+    dilate_time_w.write(DilateTime::freeze(PARRY_HITSTOP_MS).with_priority(1));
+    shake.add_trauma(0.35);
+    bloom_flash.0 = PARRY_BLOOM_FLASH;
+    cmd.spawn_particles(
+        PoolKey::CircleParticles,
+        (
+            particles.circle_particle_spawner(),
+            ParticleEffectHandle(particles.reflection.clone_weak()),
+            Transform::from_translation(projectile_t.translation().with_z(10.))
+                .with_rotation(move_dir.0.to_quat()),
+        ),
+    );
+
+    // reward
+    ammo.offset(PARRY_AMMO_REWARD);
+    for (e, _) in ammo_ui_q.iter().filter(|(_, p)| *p == player) {
+        cmd.entity(e).try_insert(Animator::new(
+            get_relative_scale_tween(
+                Vec2::splat(1.15).extend(1.),
+                150,
+                Some(EaseFunction::BackOut),
+            )
+            .then(get_relative_scale_tween(
+                Vec3::ONE,
+                150,
+                Some(EaseFunction::QuadraticOut),
+            )),
+        ));
+    }
+
+    cmd.entity(ev.paddle_e).remove::<ParryWindow>();
+    cmd.entity(paddle.reflect_e).try_insert(Animator::new(
+        get_relative_sprite_color_tween(COL_BULLET, 40, Some(EaseFunction::QuadraticOut)).then(
+            delay_tween(
+                get_relative_sprite_color_tween(
+                    paddle_mode.color(),
+                    150,
+                    Some(EaseFunction::QuadraticIn),
+                ),
+                PARRY_HITSTOP_MS,
+            ),
+        ),
+    ));
+}
+
+fn fade_bloom_flash(mut bloom_flash: ResMut<BloomFlash>, time: Res<Time>) {
+    if bloom_flash.0 > 0. {
+        bloom_flash.0 =
+            asymptotic_smoothing_with_delta_time(bloom_flash.0, 0., 0.15, time.delta_secs());
+    }
+}
@@ -0,0 +1,253 @@
+//! Pickup collection and the timed effects they grant.
+
+use bevy::prelude::*;
+use bevy_trauma_shake::Shakes;
+use bevy_tweening::AssetAnimator;
+
+use crate::{
+    screen::{Screen, in_game_state},
+    theme::palette::COL_PICKUP_SHIELD,
+};
+
+use super::{
+    assets::SpriteAssets,
+    core::RepairGear,
+    spawn::{
+        ball::{BALL_BASE_RADIUS, Ball},
+        level::{CORE_RADIUS, Core},
+        paddle::{Paddle, PaddleAmmo},
+        pickup::{PICKUP_RADIUS, Pickup, PickupKind},
+        projectile::{Projectile, ProjectileTarget},
+    },
+    time::{Cooldown, process_cooldown},
+    tween::{
+        DespawnOnTweenCompleted, get_relative_color_material_color_tween, get_relative_scale_anim,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Game), spawn_effect_hud)
+        .add_systems(
+            Update,
+            (
+                collect_pickups,
+                apply_big_ball,
+                fade_core_shield,
+                update_effect_hud,
+                process_cooldown::<MultiShot>,
+                process_cooldown::<BigBall>,
+                process_cooldown::<CoreShield>,
+            )
+                .run_if(in_game_state),
+        );
+}
+
+pub const PICKUP_AMMO: isize = 10;
+pub const MULTI_SHOT_DURATION_S: f32 = 8.;
+pub const BIG_BALL_DURATION_S: f32 = 10.;
+pub const BIG_BALL_SCALE: f32 = 1.5;
+pub const CORE_SHIELD_DURATION_S: f32 = 6.;
+
+/// Paddle fires two extra projectiles per shot while its [`Cooldown`] is active.
+pub struct MultiShot;
+
+/// Balls grow bigger while the paddle's [`Cooldown`] is active.
+pub struct BigBall;
+
+/// Core ignores damage while its [`Cooldown`] is active.
+pub struct CoreShield;
+
+#[derive(Component, Debug)]
+struct EffectHudEntry(PickupKind);
+
+#[derive(Component, Debug)]
+struct EffectHudText(PickupKind);
+
+fn collect_pickups(
+    pickup_q: Query<(Entity, &Pickup, &GlobalTransform)>,
+    ball_q: Query<(&Ball, &GlobalTransform)>,
+    projectile_q: Query<(&Projectile, &GlobalTransform)>,
+    mut paddle_q: Query<(Entity, &Paddle, &mut PaddleAmmo)>,
+    core_q: Query<(Entity, &Core, &GlobalTransform)>,
+    mut repair_gear_w: EventWriter<RepairGear>,
+    mut cmd: Commands,
+    mut shake: Shakes,
+) {
+    for (pickup_e, pickup, t) in &pickup_q {
+        let pos = t.translation().truncate();
+        // effects are granted to the nearest core and its paddles
+        let Some((core_e, core, core_t)) = core_q.iter().min_by(|(_, _, a), (_, _, b)| {
+            let dist = |t: &GlobalTransform| t.translation().truncate().distance_squared(pos);
+            dist(a).total_cmp(&dist(b))
+        }) else {
+            continue;
+        };
+        let collected = core_t.translation().truncate().distance(pos) < CORE_RADIUS
+            || ball_q.iter().any(|(ball, ball_t)| {
+                ball_t.translation().truncate().distance(pos) < ball.radius + PICKUP_RADIUS
+            })
+            || projectile_q.iter().any(|(projectile, projectile_t)| {
+                projectile.target == ProjectileTarget::Enemy
+                    && projectile_t.translation().truncate().distance(pos) < PICKUP_RADIUS
+            });
+        if !collected {
+            continue;
+        }
+
+        match pickup.kind {
+            PickupKind::Ammo => {
+                for (.., mut ammo) in paddle_q.iter_mut().filter(|(_, p, _)| p.core_e == core_e) {
+                    ammo.offset(PICKUP_AMMO);
+                }
+            }
+            PickupKind::GearRepair => {
+                repair_gear_w.write(RepairGear(core_e));
+            }
+            PickupKind::MultiShot => {
+                for (paddle_e, ..) in paddle_q.iter().filter(|(_, p, _)| p.core_e == core_e) {
+                    cmd.entity(paddle_e)
+                        .try_insert(Cooldown::<MultiShot>::new(MULTI_SHOT_DURATION_S));
+                }
+            }
+            PickupKind::BigBall => {
+                for (paddle_e, ..) in paddle_q.iter().filter(|(_, p, _)| p.core_e == core_e) {
+                    cmd.entity(paddle_e)
+                        .try_insert(Cooldown::<BigBall>::new(BIG_BALL_DURATION_S));
+                }
+            }
+            PickupKind::CoreShield => {
+                cmd.entity(core_e)
+                    .try_insert(Cooldown::<CoreShield>::new(CORE_SHIELD_DURATION_S));
+                cmd.entity(core.clear_mesh_e).insert(AssetAnimator::new(
+                    get_relative_color_material_color_tween(
+                        COL_PICKUP_SHIELD.with_alpha(0.15),
+                        250,
+                        Some(EaseFunction::QuadraticOut),
+                    ),
+                ));
+            }
+        }
+
+        shake.add_trauma(0.1);
+        cmd.entity(pickup_e).remove::<Pickup>().try_insert((
+            get_relative_scale_anim(Vec2::ZERO.extend(1.), 120, Some(EaseFunction::BackIn)),
+            DespawnOnTweenCompleted::Itself,
+        ));
+    }
+}
+
+fn apply_big_ball(
+    paddle_q: Query<Has<Cooldown<BigBall>>, With<Paddle>>,
+    mut ball_q: Query<(&mut Ball, &mut Transform)>,
+) {
+    let radius = if paddle_q.iter().any(|big| big) {
+        BALL_BASE_RADIUS * BIG_BALL_SCALE
+    } else {
+        BALL_BASE_RADIUS
+    };
+    for (mut ball, mut t) in &mut ball_q {
+        if ball.radius != radius {
+            ball.radius = radius;
+            t.scale = Vec2::splat(radius / BALL_BASE_RADIUS).extend(1.);
+        }
+    }
+}
+
+fn fade_core_shield(
+    mut removed: RemovedComponents<Cooldown<CoreShield>>,
+    core_q: Query<&Core>,
+    mut cmd: Commands,
+) {
+    for e in removed.read() {
+        if let Ok(core) = core_q.get(e) {
+            cmd.entity(core.clear_mesh_e).insert(AssetAnimator::new(
+                get_relative_color_material_color_tween(
+                    Color::NONE,
+                    400,
+                    Some(EaseFunction::QuadraticIn),
+                ),
+            ));
+        }
+    }
+}
+
+fn spawn_effect_hud(mut cmd: Commands, sprites: Res<SpriteAssets>) {
+    cmd.spawn((
+        Name::new("effect_hud"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.),
+            left: Val::Px(16.),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(6.),
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Game),
+    ))
+    .with_children(|b| {
+        for kind in [
+            PickupKind::MultiShot,
+            PickupKind::BigBall,
+            PickupKind::CoreShield,
+        ] {
+            b.spawn((
+                Name::new("effect_hud_entry"),
+                EffectHudEntry(kind),
+                Node {
+                    display: Display::None,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.),
+                    ..default()
+                },
+            ))
+            .with_children(|b| {
+                b.spawn((
+                    Name::new("effect_hud_icon"),
+                    ImageNode::new(kind.image(&sprites)).with_color(kind.color()),
+                    Node {
+                        width: Val::Px(28.),
+                        height: Val::Px(28.),
+                        ..default()
+                    },
+                ));
+                b.spawn((
+                    Name::new("effect_hud_text"),
+                    EffectHudText(kind),
+                    Text::default(),
+                    TextFont::from_font_size(22.),
+                    TextColor(kind.color()),
+                ));
+            });
+        }
+    });
+}
+
+fn update_effect_hud(
+    mut entry_q: Query<(&EffectHudEntry, &mut Node)>,
+    mut text_q: Query<(&EffectHudText, &mut Text)>,
+    multi_shot_q: Query<&Cooldown<MultiShot>>,
+    big_ball_q: Query<&Cooldown<BigBall>>,
+    shield_q: Query<&Cooldown<CoreShield>>,
+) {
+    let remaining = |kind: PickupKind| match kind {
+        PickupKind::MultiShot => multi_shot_q.iter().next().map(Cooldown::remaining_secs),
+        PickupKind::BigBall => big_ball_q.iter().next().map(Cooldown::remaining_secs),
+        PickupKind::CoreShield => shield_q.iter().next().map(Cooldown::remaining_secs),
+        PickupKind::Ammo | PickupKind::GearRepair => None,
+    };
+
+    // inactive entries don't take up space, so the active ones stay packed together
+    for (entry, mut node) in &mut entry_q {
+        node.display = if remaining(entry.0).is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    for (entry, mut text) in &mut text_q {
+        if let Some(secs) = remaining(entry.0) {
+            text.0 = format!("{} {}", entry.0.label(), secs.ceil());
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Local co-op players and their gamepads.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    AppSet,
+    theme::palette::{COL_AMMO_FILL, COL_PADDLE, COL_PADDLE_2},
+};
+
+use super::{bot::Bot, input::PlayerAction, spawn::paddle::PADDLE_RADIUS};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Multiplayer>()
+        .add_systems(Update, assign_gamepads.in_set(AppSet::ProcessInput));
+}
+
+/// Rail of the second player in [`Multiplayer::CoopInnerRail`].
+pub const INNER_RAIL_RADIUS: f32 = 230.0;
+/// Horizontal distance of each core from the arena center in [`Multiplayer::Versus`].
+pub const VERSUS_CORE_OFFSET: f32 = 410.0;
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Multiplayer {
+    #[default]
+    Off,
+    /// Both paddles share the outer rail.
+    Coop,
+    /// The second paddle rides a smaller rail inside the first one.
+    CoopInnerRail,
+    /// Each player guards their own core.
+    Versus,
+}
+
+impl Multiplayer {
+    pub fn players(&self) -> impl Iterator<Item = Player> {
+        let count = if *self == Multiplayer::Off { 1 } else { 2 };
+        (0..count).map(Player)
+    }
+
+    /// Position of every core and the players guarding it.
+    pub fn cores(&self) -> Vec<(Vec2, Vec<Player>)> {
+        match self {
+            Multiplayer::Versus => self
+                .players()
+                .map(|p| {
+                    let sign = if p.0 == 0 { -1. } else { 1. };
+                    (Vec2::X * VERSUS_CORE_OFFSET * sign, vec![p])
+                })
+                .collect(),
+            _ => vec![(Vec2::ZERO, self.players().collect())],
+        }
+    }
+
+    pub fn rail_radius(&self, player: Player) -> f32 {
+        match (self, player.0) {
+            (Multiplayer::CoopInnerRail, 1) => INNER_RAIL_RADIUS,
+            _ => PADDLE_RADIUS,
+        }
+    }
+}
+
+/// Index of the player controlling the paddle (or owning the UI).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Player(pub usize);
+
+impl Player {
+    pub fn color(&self) -> Color {
+        match self.0 {
+            0 => COL_PADDLE,
+            _ => COL_PADDLE_2,
+        }
+    }
+
+    pub fn ammo_color(&self) -> Color {
+        match self.0 {
+            0 => COL_AMMO_FILL,
+            _ => COL_PADDLE_2,
+        }
+    }
+}
+
+/// Second player gets the last connected gamepad,
+/// the first one keeps mouse & keyboard and gets a gamepad only if there is one to spare.
+fn assign_gamepads(
+    gamepad_q: Query<Entity, With<Gamepad>>,
+    added_gamepad_q: Query<(), Added<Gamepad>>,
+    mut removed_gamepads: RemovedComponents<Gamepad>,
+    mut map_q: Query<(&Player, &mut InputMap<PlayerAction>), Without<Bot>>,
+    added_map_q: Query<(), Added<InputMap<PlayerAction>>>,
+    multiplayer: Res<Multiplayer>,
+) {
+    let removed = removed_gamepads.read().count() > 0;
+    if added_gamepad_q.is_empty() && added_map_q.is_empty() && !removed {
+        return;
+    }
+
+    let mut gamepads: Vec<_> = gamepad_q.iter().collect();
+    gamepads.sort();
+    for (player, mut map) in &mut map_q {
+        let gamepad = match (*multiplayer, player.0) {
+            // any gamepad
+            (Multiplayer::Off, _) => None,
+            (_, 0) if gamepads.len() > 1 => gamepads.first().copied(),
+            (_, 0) => {
+                *map = PlayerAction::input_map(true, false);
+                continue;
+            }
+            _ => gamepads.last().copied(),
+        };
+        *map = PlayerAction::input_map(player.0 == 0, true);
+        if let Some(gamepad) = gamepad {
+            map.set_gamepad(gamepad);
+        }
+    }
+}
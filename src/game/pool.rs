@@ -0,0 +1,200 @@
+//! Reuses projectiles, enemies and one-shot particle spawners instead of spawning new entities.
+//!
+//! Pooled entities are parked when released: hidden, paused and without a collider, until a
+//! spawn of the same [`PoolKey`] takes them back and inserts its full state again.
+
+use avian2d::prelude::*;
+use bevy::{platform::collections::HashMap, prelude::*};
+use bevy_enoki::prelude::{OneShot, ParticleSpawnerState};
+
+use crate::screen::Screen;
+
+use super::{
+    movement::{HomingTarget, MovementPaused},
+    spawn::{
+        enemy::{Enemy, EnemyGunBarrel, EnemyKind},
+        projectile::Projectile,
+    },
+    time::{Cooldown, process_cooldown},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<EntityPool>()
+        .add_systems(OnExit(Screen::Game), clear_pool)
+        .add_systems(
+            Update,
+            (
+                process_cooldown::<OneShotParticles>,
+                release_one_shot_particles.after(process_cooldown::<OneShotParticles>),
+            ),
+        );
+}
+
+/// Time a one-shot spawner stays up, longer than the lifetime of the particles it emits.
+const ONE_SHOT_PARTICLES_S: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoolKey {
+    Projectile,
+    SquareParticles,
+    CircleParticles,
+    Enemy(EnemyKind),
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PoolStats {
+    /// New entities created by the pool.
+    pub spawned: usize,
+    pub reused: usize,
+    pub released: usize,
+}
+
+#[derive(Resource, Debug)]
+pub struct EntityPool {
+    free: HashMap<PoolKey, Vec<Entity>>,
+    /// Despawns released entities and always spawns new ones when off.
+    pub enabled: bool,
+    pub stats: PoolStats,
+}
+
+impl Default for EntityPool {
+    fn default() -> Self {
+        Self {
+            free: HashMap::default(),
+            enabled: true,
+            stats: PoolStats::default(),
+        }
+    }
+}
+
+/// Pool the entity is returned to on release.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PoolMember(pub PoolKey);
+
+/// Released entity waiting in the pool.
+#[derive(Component, Debug)]
+pub struct Parked;
+
+struct OneShotParticles;
+
+impl EntityPool {
+    /// Skips parked entities that have been despawned in the meantime.
+    fn take(&mut self, key: PoolKey, mut exists: impl FnMut(Entity) -> bool) -> Option<Entity> {
+        let free = self.free.get_mut(&key)?;
+        let e = std::iter::from_fn(|| free.pop()).find(|&e| exists(e))?;
+        self.stats.reused += 1;
+        Some(e)
+    }
+
+    /// Reuses a parked entity or spawns a new one, the caller inserts all of its state.
+    /// Returns whether the entity has been reused, its children are kept.
+    pub fn spawn(&mut self, cmd: &mut Commands, key: PoolKey) -> (Entity, bool) {
+        match self.take(key, |e| cmd.get_entity(e).is_ok()) {
+            Some(e) => {
+                unpark(&mut cmd.entity(e));
+                (e, true)
+            }
+            None => {
+                self.stats.spawned += 1;
+                (cmd.spawn(PoolMember(key)).id(), false)
+            }
+        }
+    }
+}
+
+fn unpark(entity: &mut EntityCommands) {
+    entity
+        .remove::<(Parked, MovementPaused)>()
+        .insert(Visibility::Inherited);
+}
+
+pub trait PoolCommandsExt {
+    /// Parks pooled entities until they are reused, others are despawned.
+    fn release(&mut self, e: Entity);
+
+    /// Plays a one-shot particle effect on a pooled spawner.
+    fn spawn_particles(&mut self, key: PoolKey, bundle: impl Bundle);
+}
+
+impl PoolCommandsExt for Commands<'_, '_> {
+    fn release(&mut self, e: Entity) {
+        self.queue(move |world: &mut World| release(world, e));
+    }
+
+    fn spawn_particles(&mut self, key: PoolKey, bundle: impl Bundle) {
+        self.queue(move |world: &mut World| {
+            let reused = world.resource_scope(|world, mut pool: Mut<EntityPool>| {
+                pool.take(key, |e| world.entities().contains(e))
+            });
+            let particles = (
+                bundle,
+                // restarts the spawner if it's reused
+                ParticleSpawnerState::default(),
+                OneShot::Deactivate,
+                Cooldown::<OneShotParticles>::new(ONE_SHOT_PARTICLES_S),
+                StateScoped(Screen::Game),
+            );
+            match reused {
+                Some(e) => {
+                    world
+                        .entity_mut(e)
+                        .remove::<(Parked, MovementPaused)>()
+                        .insert((Visibility::Inherited, particles));
+                }
+                None => {
+                    world.resource_mut::<EntityPool>().stats.spawned += 1;
+                    world.spawn((PoolMember(key), particles));
+                }
+            }
+        });
+    }
+}
+
+fn release(world: &mut World, e: Entity) {
+    let Ok(mut entity) = world.get_entity_mut(e) else {
+        return;
+    };
+    let Some(&PoolMember(key)) = entity.get::<PoolMember>() else {
+        entity.despawn();
+        return;
+    };
+    if entity.contains::<Parked>() {
+        return;
+    }
+    if !world.resource::<EntityPool>().enabled {
+        world.despawn(e);
+        return;
+    }
+
+    world
+        .entity_mut(e)
+        .remove::<(Collider, HomingTarget, Projectile, Enemy)>()
+        .insert((Parked, Visibility::Hidden, MovementPaused));
+    // parked barrels would keep firing from wherever the enemy was released
+    let children = world
+        .get::<Children>(e)
+        .map(|c| c.to_vec())
+        .unwrap_or_default();
+    for child_e in children {
+        if let Some(mut barrel) = world.get_mut::<EnemyGunBarrel>(child_e) {
+            *barrel = EnemyGunBarrel::Inactive;
+        }
+    }
+    let mut pool = world.resource_mut::<EntityPool>();
+    pool.free.entry(key).or_default().push(e);
+    pool.stats.released += 1;
+}
+
+fn release_one_shot_particles(
+    mut removed: RemovedComponents<Cooldown<OneShotParticles>>,
+    mut cmd: Commands,
+) {
+    for e in removed.read() {
+        cmd.release(e);
+    }
+}
+
+/// Parked entities are state scoped as well, they are gone after the game.
+fn clear_pool(mut pool: ResMut<EntityPool>) {
+    pool.free.clear();
+}
@@ -0,0 +1,314 @@
+//! Scrap earned from runs and the starting options it unlocks.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{screen::Screen, theme::palette::COL_ENEMY};
+
+use super::{
+    bot::BotPlayers,
+    mode::{GameMode, RunRules},
+    spawn::enemy::EnemySet,
+    stats::RunStats,
+    tutorial::TutorialRun,
+    weapon::WeaponKind,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Progression>()
+        .add_systems(OnEnter(Screen::Game), reset_last_run_scrap)
+        .add_systems(OnExit(Screen::Game), award_scrap.run_if(progress_enabled));
+}
+
+/// Survival time worth a single scrap.
+const SCRAP_SURVIVAL_S: f32 = 10.;
+
+/// Saved by the snake case name, renaming a variant needs a save migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unlock {
+    AmmoCache,
+    BigAmmoCache,
+    SpreadShot,
+    BurstRifle,
+    PiercingRail,
+    HeavyCannon,
+    EmberSkin,
+    TurncoatSkin,
+    BallOnlyMode,
+    HardcoreMode,
+    SwarmSet,
+    ArtillerySet,
+}
+
+impl Unlock {
+    pub const ALL: [Unlock; 12] = [
+        Unlock::AmmoCache,
+        Unlock::BigAmmoCache,
+        Unlock::SpreadShot,
+        Unlock::BurstRifle,
+        Unlock::PiercingRail,
+        Unlock::HeavyCannon,
+        Unlock::EmberSkin,
+        Unlock::TurncoatSkin,
+        Unlock::BallOnlyMode,
+        Unlock::HardcoreMode,
+        Unlock::SwarmSet,
+        Unlock::ArtillerySet,
+    ];
+
+    pub fn def(&self) -> UnlockDef {
+        match self {
+            Unlock::AmmoCache => UnlockDef {
+                name: "AMMO CACHE",
+                cost: 40,
+                requires: None,
+                reward: UnlockReward::StartingAmmo(10),
+            },
+            Unlock::BigAmmoCache => UnlockDef {
+                name: "BIG AMMO CACHE",
+                cost: 120,
+                requires: Some(Unlock::AmmoCache),
+                reward: UnlockReward::StartingAmmo(25),
+            },
+            Unlock::SpreadShot => UnlockDef {
+                name: "SPREAD SHOT",
+                cost: 60,
+                requires: None,
+                reward: UnlockReward::Weapon(WeaponKind::SpreadShot),
+            },
+            Unlock::BurstRifle => UnlockDef {
+                name: "BURST RIFLE",
+                cost: 100,
+                requires: Some(Unlock::SpreadShot),
+                reward: UnlockReward::Weapon(WeaponKind::BurstRifle),
+            },
+            Unlock::PiercingRail => UnlockDef {
+                name: "PIERCING RAIL",
+                cost: 150,
+                requires: Some(Unlock::BurstRifle),
+                reward: UnlockReward::Weapon(WeaponKind::PiercingRail),
+            },
+            Unlock::HeavyCannon => UnlockDef {
+                name: "HEAVY CANNON",
+                cost: 220,
+                requires: Some(Unlock::PiercingRail),
+                reward: UnlockReward::Weapon(WeaponKind::HeavyCannon),
+            },
+            Unlock::EmberSkin => UnlockDef {
+                name: "EMBER PADDLE",
+                cost: 50,
+                requires: None,
+                reward: UnlockReward::Skin(PaddleSkin::Ember),
+            },
+            Unlock::TurncoatSkin => UnlockDef {
+                name: "TURNCOAT PADDLE",
+                cost: 150,
+                requires: Some(Unlock::EmberSkin),
+                reward: UnlockReward::Skin(PaddleSkin::Turncoat),
+            },
+            Unlock::BallOnlyMode => UnlockDef {
+                name: "BALL ONLY MODE",
+                cost: 80,
+                requires: None,
+                reward: UnlockReward::Mode(GameMode::BallOnly),
+            },
+            Unlock::HardcoreMode => UnlockDef {
+                name: "HARDCORE MODE",
+                cost: 200,
+                requires: Some(Unlock::BallOnlyMode),
+                reward: UnlockReward::Mode(GameMode::Hardcore),
+            },
+            Unlock::SwarmSet => UnlockDef {
+                name: "SWARM ENEMIES",
+                cost: 90,
+                requires: None,
+                reward: UnlockReward::EnemySet(EnemySet::Swarm),
+            },
+            Unlock::ArtillerySet => UnlockDef {
+                name: "ARTILLERY ENEMIES",
+                cost: 160,
+                requires: Some(Unlock::SwarmSet),
+                reward: UnlockReward::EnemySet(EnemySet::Artillery),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UnlockDef {
+    pub name: &'static str,
+    /// Price in scrap.
+    pub cost: usize,
+    /// Unlock that has to be bought first.
+    pub requires: Option<Unlock>,
+    pub reward: UnlockReward,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnlockReward {
+    /// Ammo the paddle starts with, the biggest owned one applies.
+    StartingAmmo(usize),
+    Weapon(WeaponKind),
+    Skin(PaddleSkin),
+    Mode(GameMode),
+    EnemySet(EnemySet),
+}
+
+impl UnlockReward {
+    /// Rewards that have to be equipped, only one per slot can be.
+    pub fn equippable(&self) -> bool {
+        matches!(
+            self,
+            UnlockReward::Weapon(_) | UnlockReward::Skin(_) | UnlockReward::EnemySet(_)
+        )
+    }
+
+    fn same_slot(&self, other: &UnlockReward) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddleSkin {
+    Ember,
+    Turncoat,
+}
+
+impl PaddleSkin {
+    pub fn color(&self) -> Color {
+        match self {
+            PaddleSkin::Ember => Color::srgb(0.92, 0.42, 0.25),
+            PaddleSkin::Turncoat => COL_ENEMY,
+        }
+    }
+}
+
+/// Scrap and unlocks, kept between runs.
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Progression {
+    pub scrap: usize,
+    owned: Vec<Unlock>,
+    equipped: Vec<Unlock>,
+    /// Scrap earned by the last finished run.
+    #[serde(skip)]
+    pub last_run_scrap: usize,
+}
+
+impl Progression {
+    pub fn owns(&self, unlock: Unlock) -> bool {
+        self.owned.contains(&unlock)
+    }
+
+    pub fn is_equipped(&self, unlock: Unlock) -> bool {
+        self.equipped.contains(&unlock)
+    }
+
+    pub fn can_buy(&self, unlock: Unlock) -> bool {
+        let def = unlock.def();
+        !self.owns(unlock)
+            && self.scrap >= def.cost
+            && def.requires.is_none_or(|required| self.owns(required))
+    }
+
+    pub fn buy(&mut self, unlock: Unlock) {
+        if !self.can_buy(unlock) {
+            return;
+        }
+        self.scrap -= unlock.def().cost;
+        self.owned.push(unlock);
+    }
+
+    /// Equips an owned unlock in place of the one in the same slot, or unequips it if it already is.
+    pub fn toggle_equipped(&mut self, unlock: Unlock) {
+        let reward = unlock.def().reward;
+        if !self.owns(unlock) || !reward.equippable() {
+            return;
+        }
+        let was_equipped = self.is_equipped(unlock);
+        self.equipped.retain(|u| !u.def().reward.same_slot(&reward));
+        if !was_equipped {
+            self.equipped.push(unlock);
+        }
+    }
+
+    fn equipped_rewards(&self) -> impl Iterator<Item = UnlockReward> + '_ {
+        self.equipped
+            .iter()
+            .filter(|u| self.owns(**u))
+            .map(|u| u.def().reward)
+    }
+
+    pub fn starting_ammo(&self) -> usize {
+        self.owned
+            .iter()
+            .filter_map(|u| match u.def().reward {
+                UnlockReward::StartingAmmo(ammo) => Some(ammo),
+                _ => None,
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn weapon(&self) -> Option<WeaponKind> {
+        self.equipped_rewards().find_map(|reward| match reward {
+            UnlockReward::Weapon(kind) => Some(kind),
+            _ => None,
+        })
+    }
+
+    pub fn skin(&self) -> Option<PaddleSkin> {
+        self.equipped_rewards().find_map(|reward| match reward {
+            UnlockReward::Skin(skin) => Some(skin),
+            _ => None,
+        })
+    }
+
+    pub fn enemy_set(&self) -> Option<EnemySet> {
+        self.equipped_rewards().find_map(|reward| match reward {
+            UnlockReward::EnemySet(set) => Some(set),
+            _ => None,
+        })
+    }
+
+    /// Weapons behind an unlock can't be switched to until it's bought.
+    pub fn weapon_unlocked(&self, kind: WeaponKind) -> bool {
+        Unlock::ALL
+            .into_iter()
+            .find(|u| u.def().reward == UnlockReward::Weapon(kind))
+            .is_none_or(|u| self.owns(u))
+    }
+
+    /// Modes behind an unlock can't be picked until it's bought.
+    pub fn mode_unlocked(&self, mode: GameMode) -> bool {
+        Unlock::ALL
+            .into_iter()
+            .find(|u| u.def().reward == UnlockReward::Mode(mode))
+            .is_none_or(|u| self.owns(u))
+    }
+}
+
+/// Bots, the tutorial, the sandbox and runs that can't be lost, like zen,
+/// don't count towards scrap and achievements.
+pub fn progress_enabled(
+    bots: Res<BotPlayers>,
+    tutorial: Res<TutorialRun>,
+    rules: Res<RunRules>,
+) -> bool {
+    bots.0.is_none() && !tutorial.0 && !rules.sandbox_tools && rules.core_damage
+}
+
+pub fn scrap_for_run(stats: &RunStats) -> usize {
+    stats.total_kills() + (stats.survival_s / SCRAP_SURVIVAL_S) as usize
+}
+
+/// Isn't saved, so it doesn't mark the progression as changed.
+fn reset_last_run_scrap(mut progression: ResMut<Progression>) {
+    progression.bypass_change_detection().last_run_scrap = 0;
+}
+
+fn award_scrap(mut progression: ResMut<Progression>, stats: Res<RunStats>) {
+    let scrap = scrap_for_run(&stats);
+    progression.last_run_scrap = scrap;
+    progression.scrap += scrap;
+}
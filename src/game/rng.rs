@@ -0,0 +1,38 @@
+//! Seeded randomness for everything that should be reproducible within a run.
+
+use bevy::prelude::*;
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RunSeed>()
+        .insert_resource(RunRng(StdRng::from_entropy()))
+        .insert_resource(DropRng(StdRng::from_entropy()))
+        .add_systems(OnEnter(Screen::Game), reset_run_rng);
+}
+
+/// Seed of the next run, a random one is picked when `None`.
+#[derive(Resource, Debug, Default)]
+pub struct RunSeed(pub Option<u64>);
+
+/// Mixed into the run seed for the drop stream, so it doesn't mirror the spawn stream.
+const DROP_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Drives spawns, so runs with the same seed get the same enemy sequence.
+/// Only the spawner and enemy spawns draw from it, whatever the player does.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct RunRng(pub StdRng);
+
+/// Drives pickup drops, which are rolled on kills and so depend on how the run is played.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct DropRng(pub StdRng);
+
+fn reset_run_rng(seed: Res<RunSeed>, mut rng: ResMut<RunRng>, mut drop_rng: ResMut<DropRng>) {
+    let seeded = |salt: u64| match seed.0 {
+        Some(seed) => StdRng::seed_from_u64(seed ^ salt),
+        None => StdRng::from_entropy(),
+    };
+    rng.0 = seeded(0);
+    drop_rng.0 = seeded(DROP_SEED_SALT);
+}
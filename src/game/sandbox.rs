@@ -0,0 +1,248 @@
+//! Sandbox tools for practicing without the pressure of a real run.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    screen::{Screen, in_game_state},
+    theme::palette::{COL_ENEMY, LABEL_TEXT},
+};
+
+use super::{
+    ball::{BALL_BASE_SPEED, BALL_MAX_SPEED, BallSpeed},
+    mode::{apply_rules, sandbox_tools},
+    spawn::{
+        enemy::{EnemyKind, SpawnEnemy},
+        level::Core,
+        paddle::{PADDLE_RADIUS, PaddleAmmo},
+    },
+    time::TimeDilation,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(InputManagerPlugin::<SandboxAction>::default())
+        .init_resource::<ActionState<SandboxAction>>()
+        .insert_resource(SandboxAction::input_map())
+        .init_resource::<SandboxSettings>()
+        .add_systems(
+            OnEnter(Screen::Game),
+            spawn_sandbox_panel.run_if(sandbox_tools).after(apply_rules),
+        )
+        .add_systems(
+            Update,
+            (
+                process_sandbox_input,
+                update_sandbox_panel,
+                draw_spawn_marker,
+            )
+                .chain()
+                .run_if(in_game_state.and(sandbox_tools)),
+        );
+}
+
+/// Enemies that can be spawned from the sandbox.
+const SPAWNABLE_KINDS: [EnemyKind; 4] = [
+    EnemyKind::Creepinek,
+    EnemyKind::Shieldy,
+    EnemyKind::BigBoi,
+    EnemyKind::BangBang,
+];
+const ANGLE_STEP: f32 = 15.;
+const DISTANCE_STEP: f32 = 50.;
+const MIN_SPAWN_DISTANCE: f32 = PADDLE_RADIUS + 100.;
+const MAX_SPAWN_DISTANCE: f32 = 1200.;
+const BALL_SPEED_STEP: f32 = BALL_BASE_SPEED * 0.5;
+const SLOW_MOTION_SCALE: f32 = 0.3;
+
+#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+pub enum SandboxAction {
+    NextEnemy,
+    RotateSpawnCcw,
+    RotateSpawnCw,
+    MoveSpawnCloser,
+    MoveSpawnFurther,
+    Spawn,
+    SlowerBall,
+    FasterBall,
+    RefillAmmo,
+    ToggleSlowMotion,
+}
+
+impl SandboxAction {
+    fn input_map() -> InputMap<Self> {
+        InputMap::new([
+            (Self::NextEnemy, KeyCode::Tab),
+            (Self::RotateSpawnCcw, KeyCode::ArrowLeft),
+            (Self::RotateSpawnCw, KeyCode::ArrowRight),
+            (Self::MoveSpawnCloser, KeyCode::ArrowDown),
+            (Self::MoveSpawnFurther, KeyCode::ArrowUp),
+            (Self::Spawn, KeyCode::Enter),
+            (Self::SlowerBall, KeyCode::BracketLeft),
+            (Self::FasterBall, KeyCode::BracketRight),
+            (Self::RefillAmmo, KeyCode::KeyG),
+            (Self::ToggleSlowMotion, KeyCode::KeyT),
+        ])
+    }
+}
+
+#[derive(Resource, Debug)]
+struct SandboxSettings {
+    kind_index: usize,
+    angle: f32,
+    distance: f32,
+    ball_speed: f32,
+    slow_motion: bool,
+}
+
+impl Default for SandboxSettings {
+    fn default() -> Self {
+        Self {
+            kind_index: 0,
+            angle: 90.,
+            distance: 800.,
+            ball_speed: BALL_BASE_SPEED,
+            slow_motion: false,
+        }
+    }
+}
+
+impl SandboxSettings {
+    fn kind(&self) -> EnemyKind {
+        SPAWNABLE_KINDS[self.kind_index]
+    }
+
+    fn spawn_offset(&self) -> Vec2 {
+        Rot2::degrees(self.angle) * Vec2::X * self.distance
+    }
+}
+
+#[derive(Component, Debug)]
+struct SandboxPanelText;
+
+fn spawn_sandbox_panel(mut cmd: Commands, mut settings: ResMut<SandboxSettings>) {
+    *settings = SandboxSettings::default();
+    cmd.spawn((
+        Name::new("sandbox_panel"),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(16.),
+            left: Val::Px(16.),
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Game),
+        children![(
+            SandboxPanelText,
+            Text::default(),
+            TextFont::from_font_size(18.),
+            TextColor(LABEL_TEXT),
+        )],
+    ));
+}
+
+fn process_sandbox_input(
+    input: Res<ActionState<SandboxAction>>,
+    mut settings: ResMut<SandboxSettings>,
+    core_q: Query<(Entity, &GlobalTransform), With<Core>>,
+    mut ball_speed_q: Query<&mut BallSpeed>,
+    mut ammo_q: Query<&mut PaddleAmmo>,
+    mut dilation: ResMut<TimeDilation>,
+    mut cmd: Commands,
+) {
+    if input.just_pressed(&SandboxAction::NextEnemy) {
+        settings.kind_index = (settings.kind_index + 1) % SPAWNABLE_KINDS.len();
+    }
+    if input.just_pressed(&SandboxAction::RotateSpawnCcw) {
+        settings.angle = (settings.angle + ANGLE_STEP).rem_euclid(360.);
+    }
+    if input.just_pressed(&SandboxAction::RotateSpawnCw) {
+        settings.angle = (settings.angle - ANGLE_STEP).rem_euclid(360.);
+    }
+    if input.just_pressed(&SandboxAction::MoveSpawnCloser) {
+        settings.distance = (settings.distance - DISTANCE_STEP).max(MIN_SPAWN_DISTANCE);
+    }
+    if input.just_pressed(&SandboxAction::MoveSpawnFurther) {
+        settings.distance = (settings.distance + DISTANCE_STEP).min(MAX_SPAWN_DISTANCE);
+    }
+    if input.just_pressed(&SandboxAction::Spawn)
+        && let Ok((core_e, core_t)) = core_q.single()
+    {
+        cmd.trigger(SpawnEnemy {
+            kind: settings.kind(),
+            position: core_t.translation().truncate() + settings.spawn_offset(),
+            core_e,
+            garbage: false,
+        });
+    }
+
+    let ball_speed_delta = if input.just_pressed(&SandboxAction::SlowerBall) {
+        -BALL_SPEED_STEP
+    } else if input.just_pressed(&SandboxAction::FasterBall) {
+        BALL_SPEED_STEP
+    } else {
+        0.
+    };
+    if ball_speed_delta != 0. {
+        settings.ball_speed =
+            (settings.ball_speed + ball_speed_delta).clamp(BALL_BASE_SPEED, BALL_MAX_SPEED);
+        for mut ball_speed in &mut ball_speed_q {
+            ball_speed.0 = settings.ball_speed;
+        }
+    }
+
+    if input.just_pressed(&SandboxAction::RefillAmmo) {
+        for mut ammo in &mut ammo_q {
+            ammo.refill();
+        }
+    }
+    if input.just_pressed(&SandboxAction::ToggleSlowMotion) {
+        settings.slow_motion = !settings.slow_motion;
+        dilation.base_scale = if settings.slow_motion {
+            SLOW_MOTION_SCALE
+        } else {
+            1.
+        };
+    }
+}
+
+fn update_sandbox_panel(
+    mut text_q: Query<&mut Text, With<SandboxPanelText>>,
+    settings: Res<SandboxSettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for mut text in &mut text_q {
+        text.0 = [
+            format!("[TAB] ENEMY: {}", settings.kind().name()),
+            format!("[LEFT/RIGHT] ANGLE: {:.0}", settings.angle),
+            format!("[UP/DOWN] DISTANCE: {:.0}", settings.distance),
+            "[ENTER] SPAWN".to_string(),
+            format!("[ [ / ] ] BALL SPEED: {:.0}", settings.ball_speed),
+            "[G] REFILL AMMO".to_string(),
+            format!(
+                "[T] SLOW MOTION: {}",
+                if settings.slow_motion { "ON" } else { "OFF" }
+            ),
+        ]
+        .join("\n");
+    }
+}
+
+fn draw_spawn_marker(
+    mut gizmos: Gizmos,
+    settings: Res<SandboxSettings>,
+    core_q: Query<&GlobalTransform, With<Core>>,
+) {
+    if let Ok(core_t) = core_q.single() {
+        let core_pos = core_t.translation().truncate();
+        let spawn_pos = core_pos + settings.spawn_offset();
+        gizmos.circle_2d(Isometry2d::from_translation(spawn_pos), 40., COL_ENEMY);
+        gizmos.line_2d(
+            core_pos + settings.spawn_offset().normalize() * PADDLE_RADIUS,
+            spawn_pos,
+            COL_ENEMY.with_alpha(0.3),
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use avian2d::prelude::*;
 use bevy::prelude::*;
 use bevy_enoki::ParticleEffectHandle;
 use bevy_tweening::Animator;
@@ -6,14 +7,15 @@ use crate::{
     game::{
         assets::{ParticleAssets, SpriteAssets},
         ball::{BALL_BASE_SPEED, BallSpeed},
+        collision::GameLayer,
         movement::{MoveDirection, MovementPaused, Speed},
-        tween::{delay_tween, get_relative_scale_tween},
+        tween::{delay_tween, get_relative_scale_tween, get_relative_sprite_color_anim},
     },
     screen::Screen,
     theme::palette::COL_BALL,
 };
 
-use super::paddle::PaddleMode;
+use super::paddle::{Paddle, PaddleMode};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(spawn_ball);
@@ -30,7 +32,8 @@ pub struct SpawnBall {
 #[derive(Component, Debug)]
 pub struct Ball {
     pub radius: f32,
-    pub last_reflection_time: f32,
+    /// Core of the paddle which released the captured ball, other cores take damage when hit.
+    pub launched_from: Option<Entity>,
     pub sprite_e: Entity,
     pub particles_e: Entity,
 }
@@ -43,7 +46,7 @@ impl Ball {
     fn new(sprite_e: Entity, particles_e: Entity) -> Self {
         Self {
             radius: BALL_BASE_RADIUS,
-            last_reflection_time: 0.,
+            launched_from: None,
             sprite_e,
             particles_e,
         }
@@ -54,7 +57,7 @@ fn spawn_ball(
     trigger: Trigger<SpawnBall>,
     mut cmd: Commands,
     ball_q: Query<Entity, With<Ball>>,
-    mut paddle_q: Query<&mut PaddleMode>,
+    mut paddle_q: Query<(Entity, &Paddle, &mut PaddleMode)>,
     sprites: Res<SpriteAssets>,
     particles: Res<ParticleAssets>,
 ) {
@@ -63,7 +66,20 @@ fn spawn_ball(
     }
 
     let ev = trigger.event();
-    if let Ok(mut paddle_mode) = paddle_q.get_mut(ev.paddle_e) {
+    // release the old ball from other paddles
+    for (paddle_e, paddle, mut paddle_mode) in &mut paddle_q {
+        if paddle_e != ev.paddle_e && matches!(*paddle_mode, PaddleMode::Captured { .. }) {
+            *paddle_mode = PaddleMode::Reflect;
+            cmd.entity(paddle.reflect_e)
+                .try_insert(get_relative_sprite_color_anim(
+                    paddle_mode.color(),
+                    150,
+                    Some(EaseFunction::QuadraticOut),
+                ));
+        }
+    }
+
+    if let Ok((.., mut paddle_mode)) = paddle_q.get_mut(ev.paddle_e) {
         let sprite_e = cmd
             .spawn((
                 Name::new("sprite"),
@@ -97,6 +113,12 @@ fn spawn_ball(
                 MoveDirection(Vec2::X),
                 Speed(BALL_BASE_SPEED),
                 MovementPaused,
+                RigidBody::Kinematic,
+                Collider::circle(BALL_BASE_RADIUS),
+                GameLayer::Ball.layers(),
+                SweptCcd::default(),
+                CollisionEventsEnabled,
+                TransformInterpolation,
                 Ball::new(sprite_e, particles_e),
                 InsidePaddleRadius,
                 StateScoped(Screen::Game),
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::GAME_SIZE;
+use crate::{
+    GAME_SIZE,
+    game::pool::{Parked, PoolCommandsExt},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Update, despawn_out_of_bounds);
@@ -10,14 +13,13 @@ pub(super) fn plugin(app: &mut App) {
 pub struct DespawnOutOfBounds;
 
 fn despawn_out_of_bounds(
-    despawn_q: Query<(Entity, &GlobalTransform), With<DespawnOutOfBounds>>,
+    despawn_q: Query<(Entity, &GlobalTransform), (With<DespawnOutOfBounds>, Without<Parked>)>,
     mut cmd: Commands,
-) -> Result {
+) {
     let treshold = GAME_SIZE + 150.;
     for (e, t) in &despawn_q {
         if t.translation().truncate().max_element() > treshold {
-            cmd.get_entity(e)?.despawn();
+            cmd.release(e);
         }
     }
-    Ok(())
 }
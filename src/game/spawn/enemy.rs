@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use avian2d::prelude::*;
 use bevy::prelude::*;
-use bevy_enoki::{ParticleEffectHandle, prelude::OneShot};
+use bevy_enoki::ParticleEffectHandle;
 use bevy_tweening::Animator;
 use rand::{distributions::WeightedIndex, prelude::*};
 
@@ -10,27 +10,45 @@ use crate::{
     GAME_SIZE,
     game::{
         assets::{ParticleAssets, SpriteAssets},
-        movement::{Damping, HomingTarget, MoveDirection, Speed, SpeedMultiplier},
-        score::Score,
+        collision::GameLayer,
+        mode::{RunRules, spawner_enabled},
+        movement::{Damping, HomingTarget, Impulse, MoveDirection, Speed, SpeedMultiplier},
+        pool::{EntityPool, PoolCommandsExt, PoolKey},
+        rng::{DropRng, RunRng},
+        time::Cooldown,
         tween::{
             DespawnOnTweenCompleted, delay_tween, get_relative_scale_anim,
             get_relative_sprite_color_tween,
         },
+        versus::Garbage,
     },
     math::inverse_lerp_clamped,
     screen::Screen,
     theme::palette::{COL_ENEMY, COL_ENEMY_FLASH},
 };
 
-use super::{level::Health, paddle::PADDLE_RADIUS};
+use super::{
+    level::{Core, Health},
+    paddle::PADDLE_RADIUS,
+    pickup::roll_pickup_drop,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(spawn_enemy);
     app.add_event::<DespawnEnemy>()
+        .add_event::<EnemyKilled>()
+        .init_resource::<SpawnTimer>()
+        .init_resource::<SpawnCount>()
+        .add_systems(OnEnter(Screen::Game), reset_spawner)
         .add_systems(Last, despawn_enemy)
         .add_systems(
             Update,
-            (spawner, enemy_flash_on_hit, slow_down_near_core).run_if(in_state(Screen::Game)),
+            (
+                spawner.run_if(spawner_enabled),
+                enemy_flash_on_hit,
+                slow_down_near_core,
+            )
+                .run_if(in_state(Screen::Game)),
         );
 }
 
@@ -38,15 +56,36 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Event, Debug)]
 pub struct DespawnEnemy(pub Entity);
 
+/// An enemy has been destroyed by the player.
+#[derive(Event, Debug)]
+pub struct EnemyKilled {
+    pub kind: EnemyKind,
+    pub by: KillSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillSource {
+    Gun,
+    Ball,
+    /// Enemy projectile sent back by a parry.
+    ReflectedProjectile,
+}
+
 #[derive(Event, Debug)]
 pub struct SpawnEnemy {
     pub kind: EnemyKind,
     pub position: Vec2,
+    /// Core the enemy is heading to.
+    pub core_e: Entity,
+    /// Sent by the opponent in versus.
+    pub garbage: bool,
 }
 
 #[derive(Component, Debug, Clone)]
 pub struct Enemy {
+    pub kind: EnemyKind,
     pub sprite_e: Entity,
+    pub core_e: Entity,
 }
 
 #[derive(Component, Debug, Clone, PartialEq, Eq)]
@@ -61,7 +100,7 @@ pub struct Shielded;
 #[derive(Component, Debug, Clone)]
 pub struct StopNearCore(f32);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EnemyKind {
     Creepinek,
     Shieldy,
@@ -71,6 +110,24 @@ pub enum EnemyKind {
 }
 
 impl EnemyKind {
+    pub const ALL: [EnemyKind; 5] = [
+        EnemyKind::Creepinek,
+        EnemyKind::Shieldy,
+        EnemyKind::BigBoi,
+        EnemyKind::BangBang,
+        EnemyKind::ShieldedBang,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyKind::Creepinek => "CREEPINEK",
+            EnemyKind::Shieldy => "SHIELDY",
+            EnemyKind::BigBoi => "BIG BOI",
+            EnemyKind::BangBang => "BANG BANG",
+            EnemyKind::ShieldedBang => "SHIELDED BANG",
+        }
+    }
+
     fn base_speed(&self) -> f32 {
         match self {
             EnemyKind::Creepinek => 35.,
@@ -92,49 +149,121 @@ impl EnemyKind {
     }
 }
 
-fn spawner(mut cmd: Commands, mut next_timer: Local<Timer>, time: Res<Time>, score: Res<Score>) {
+const SPAWNABLE_KINDS: [EnemyKind; 5] = [
+    EnemyKind::Creepinek,
+    EnemyKind::Shieldy,
+    EnemyKind::BangBang,
+    EnemyKind::BigBoi,
+    EnemyKind::ShieldedBang,
+];
+
+/// Mix of enemies the spawner picks from as more of them are spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnemySet {
+    #[default]
+    Standard,
+    /// Mostly weak enemies, no gunners.
+    Swarm,
+    /// Gunners show up early and often.
+    Artillery,
+}
+
+impl EnemySet {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemySet::Standard => "STANDARD",
+            EnemySet::Swarm => "SWARM",
+            EnemySet::Artillery => "ARTILLERY",
+        }
+    }
+
+    /// Weights of [`SPAWNABLE_KINDS`] after the given number of spawned enemies.
+    fn weights(&self, spawned: usize) -> [u32; 5] {
+        match self {
+            EnemySet::Standard => match spawned {
+                // 0..=2 => [0, 0, 1, 0, 0],
+                0..=2 => [1, 0, 0, 0, 0],
+                3..=10 => [5, 2, 0, 0, 0],
+                11..=22 => [4, 1, 1, 0, 0],
+                23.. => [4, 1, 1, 1, 0],
+                // 23..=36 => [4, 1, 1, 1, 0],
+                // 37..=50 => [4, 1, 1, 1, 1],
+                // 51..=65 => [3, 1, 1, 1, 1],
+                // 66.. => [3, 2, 2, 1, 1],
+            },
+            EnemySet::Swarm => match spawned {
+                0..=10 => [1, 0, 0, 0, 0],
+                11.. => [6, 2, 0, 0, 0],
+            },
+            EnemySet::Artillery => match spawned {
+                0..=2 => [2, 0, 1, 0, 0],
+                3..=22 => [2, 1, 2, 0, 0],
+                23.. => [2, 1, 3, 1, 0],
+            },
+        }
+    }
+}
+
+/// Time until the next enemy spawn.
+#[derive(Resource, Debug, Default)]
+struct SpawnTimer(Timer);
+
+/// Enemies spawned by the spawner this run, the difficulty ramps up with it.
+/// Unlike the score it doesn't depend on the player, so a seeded run always gets the same enemies.
+#[derive(Resource, Debug, Default)]
+struct SpawnCount(usize);
+
+fn reset_spawner(mut timer: ResMut<SpawnTimer>, mut count: ResMut<SpawnCount>) {
+    *timer = SpawnTimer::default();
+    *count = SpawnCount::default();
+}
+
+fn spawner(
+    mut cmd: Commands,
+    mut next_timer: ResMut<SpawnTimer>,
+    time: Res<Time>,
+    mut count: ResMut<SpawnCount>,
+    core_q: Query<(Entity, &GlobalTransform), With<Core>>,
+    rules: Res<RunRules>,
+    mut rng: ResMut<RunRng>,
+) {
+    let next_timer = &mut next_timer.0;
     next_timer.tick(time.delta());
 
     if next_timer.just_finished() {
-        let mut rng = thread_rng();
+        let rng = &mut rng.0;
+        let cores: Vec<_> = core_q.iter().collect();
+        let Some(&(core_e, core_t)) = cores.choose(rng) else {
+            return;
+        };
+        let core_pos = core_t.translation().truncate();
         let spawn_dist = (2.0 * (GAME_SIZE / 2.0).powi(2)).sqrt() + 100.;
 
-        let spawnable_kinds = [
-            EnemyKind::Creepinek,
-            EnemyKind::Shieldy,
-            EnemyKind::BangBang,
-            EnemyKind::BigBoi,
-            EnemyKind::ShieldedBang,
-        ];
-        let weights = WeightedIndex::new(match score.0 {
-            // 0..=2 => [0, 0, 1, 0, 0],
-            0..=2 => [1, 0, 0, 0, 0],
-            3..=10 => [5, 2, 0, 0, 0],
-            11..=22 => [4, 1, 1, 0, 0],
-            23.. => [4, 1, 1, 1, 0],
-            // 23..=36 => [4, 1, 1, 1, 0],
-            // 37..=50 => [4, 1, 1, 1, 1],
-            // 51..=65 => [3, 1, 1, 1, 1],
-            // 66.. => [3, 2, 2, 1, 1],
-        })
-        .expect("Create weighted index");
+        let weights =
+            WeightedIndex::new(rules.enemy_set.weights(count.0)).expect("Create weighted index");
 
-        let kind = spawnable_kinds[weights.sample(&mut rng)];
+        let kind = rules
+            .enemy_kind
+            .unwrap_or(SPAWNABLE_KINDS[weights.sample(rng)]);
+        let offset = match kind {
+            EnemyKind::Creepinek | EnemyKind::Shieldy | EnemyKind::BigBoi => {
+                (Rot2::degrees(rng.gen_range(-360.0..360.0)) * Vec2::X).normalize() * spawn_dist
+            }
+            EnemyKind::BangBang | EnemyKind::ShieldedBang => {
+                let base_angle = Rot2::degrees(rng.gen_range(30.0..60.0));
+                let angle = base_angle * Rot2::degrees(90.0 * (rng.gen_range(0..=3) as f32));
+                (angle * Vec2::X).normalize() * spawn_dist
+            }
+        };
         cmd.trigger(SpawnEnemy {
             kind,
-            position: match kind {
-                EnemyKind::Creepinek | EnemyKind::Shieldy | EnemyKind::BigBoi => {
-                    (Rot2::degrees(rng.gen_range(-360.0..360.0)) * Vec2::X).normalize() * spawn_dist
-                }
-                EnemyKind::BangBang | EnemyKind::ShieldedBang => {
-                    let base_angle = Rot2::degrees(rng.gen_range(30.0..60.0));
-                    let angle = base_angle * Rot2::degrees(90.0 * (rng.gen_range(0..=3) as f32));
-                    (angle * Vec2::X).normalize() * spawn_dist
-                }
-            },
+            position: core_pos + offset,
+            core_e,
+            garbage: false,
         });
+        count.0 += 1;
         // todo: balance the spawn rates
-        let time_mult_range = match score.0 {
+        let time_mult_range = match count.0 {
             0..=1 => 2.5..3.0,
             2..=5 => 1.5..2.0,
             6..=15 => 1.2..1.5,
@@ -145,153 +274,179 @@ fn spawner(mut cmd: Commands, mut next_timer: Local<Timer>, time: Res<Time>, sco
             91.. => 0.3..0.5,
         };
         next_timer.set_duration(Duration::from_secs_f32(
-            kind.base_time() * rng.gen_range(time_mult_range),
+            kind.base_time() * rng.gen_range(time_mult_range) * rules.spawn_interval_mult,
         ));
         next_timer.reset();
     }
 }
 
-fn spawn_enemy(trigger: Trigger<SpawnEnemy>, mut cmd: Commands, sprites: Res<SpriteAssets>) {
-    let mut rng = thread_rng();
+fn spawn_enemy(
+    trigger: Trigger<SpawnEnemy>,
+    mut cmd: Commands,
+    sprites: Res<SpriteAssets>,
+    core_q: Query<&GlobalTransform, With<Core>>,
+    children_q: Query<&Children>,
+    barrel_q: Query<(), With<EnemyGunBarrel>>,
+    mut sprite_q: Query<&mut Sprite>,
+    mut pool: ResMut<EntityPool>,
+    mut rng: ResMut<RunRng>,
+) {
+    let rng = &mut rng.0;
 
     let ev = trigger.event();
-    let speed = rng.gen_range(ev.kind.base_speed()..(ev.kind.base_speed() * 1.5));
-    // let speed = rng.gen_range(ev.kind.base_speed()..(ev.kind.base_speed() * 1.5)) * 5.;
-
-    match ev.kind {
+    let core_pos = core_q
+        .get(ev.core_e)
+        .map(|t| t.translation().truncate())
+        .unwrap_or_default();
+    // position relative to the targeted core
+    let offset = ev.position - core_pos;
+
+    let (name, collider, health) = match ev.kind {
         EnemyKind::Creepinek => {
             let size = 45.;
             let a = Vec2::Y * size;
             let b = Vec2::new(-size, -size);
             let c = Vec2::new(size, -size);
-
-            let mesh_e = cmd
-                .spawn(Sprite {
-                    image: sprites.enemy_creepinek.clone_weak(),
-                    color: COL_ENEMY,
-                    ..default()
-                })
-                .id();
-
-            cmd.spawn((
-                Name::new("creepinek"),
-                Transform::from_translation(ev.position.extend(0.1)).with_rotation(
-                    Quat::from_rotation_z(ev.position.to_angle() + 90f32.to_radians()),
-                ),
-                Visibility::default(),
-                Collider::triangle(a, b, c),
-                MoveDirection(-ev.position.normalize_or_zero()),
-                Speed(speed),
-                HomingTarget,
-                Enemy { sprite_e: mesh_e },
-                Health(3),
-                StateScoped(Screen::Game),
-            ))
-            .add_child(mesh_e);
-        }
-        EnemyKind::Shieldy => {
-            let mesh_e = cmd
-                .spawn(Sprite {
-                    image: sprites.enemy_creepy_shield.clone_weak(),
-                    color: COL_ENEMY,
-                    ..default()
-                })
-                .id();
-
-            cmd.spawn((
-                Name::new("shieldy"),
-                Transform::from_translation(ev.position.extend(0.1)).with_rotation(
-                    Quat::from_rotation_z(ev.position.to_angle() + 90f32.to_radians()),
-                ),
-                Visibility::default(),
-                Collider::ellipse(75., 60.),
-                MoveDirection(-ev.position.normalize_or_zero()),
-                Speed(speed),
-                HomingTarget,
-                Enemy { sprite_e: mesh_e },
-                Health(3),
-                Shielded,
-                StateScoped(Screen::Game),
-            ))
-            .add_child(mesh_e);
+            ("creepinek", Collider::triangle(a, b, c), 3)
         }
+        EnemyKind::Shieldy => ("shieldy", Collider::ellipse(75., 60.), 3),
         EnemyKind::BigBoi => {
             let size = 95.;
             let a = Vec2::Y * (size - 15.);
             let b = Vec2::new(-size, -size + 10.);
             let c = Vec2::new(size, -size + 10.);
-
-            let sprite_e = cmd
-                .spawn(Sprite {
-                    image: sprites.enemy_big_boi.clone_weak(),
-                    color: COL_ENEMY,
-                    ..default()
-                })
-                .id();
-
-            cmd.spawn((
-                Name::new("big_boi"),
-                Transform::from_translation(ev.position.extend(0.1)).with_rotation(
-                    Quat::from_rotation_z(ev.position.to_angle() + 90f32.to_radians()),
-                ),
-                Visibility::default(),
-                Collider::triangle(a, b, c),
-                MoveDirection(-ev.position.normalize_or_zero()),
-                Speed(speed),
-                HomingTarget,
-                Enemy { sprite_e },
-                Health(8),
-                StateScoped(Screen::Game),
-            ))
-            .add_child(sprite_e);
+            ("big_boi", Collider::triangle(a, b, c), 8)
         }
         EnemyKind::BangBang => {
             let size = 50.;
             let a = Vec2::Y * (size + 30.);
             let b = Vec2::new(-size, -size);
             let c = Vec2::new(size, -size);
+            ("bang_bang", Collider::triangle(a, b, c), 5)
+        }
+        // not designed yet, the spawner never picks it
+        EnemyKind::ShieldedBang => {
+            warn!(kind = ?ev.kind, "enemy can't be spawned");
+            return;
+        }
+    };
 
-            let sprite_e = cmd
-                .spawn(Sprite {
-                    image: sprites.enemy_bang.clone(),
-                    color: COL_ENEMY,
-                    ..default()
-                })
-                .id();
-
-            let barrel_e = cmd
-                .spawn((
-                    Sprite {
-                        image: sprites.enemy_bang_barrel.clone(),
-                        color: COL_ENEMY,
-                        ..default()
-                    },
-                    Transform::from_translation(Vec3::Y * (size + 10.)),
-                    EnemyGunBarrel::Inactive,
-                ))
-                .id();
-
-            cmd.spawn((
-                Name::new("bang_bang"),
-                Transform::from_translation(ev.position.extend(0.1)).with_rotation(
-                    Quat::from_rotation_z(ev.position.to_angle() + 90f32.to_radians()),
-                ),
-                Visibility::default(),
-                Collider::triangle(a, b, c),
-                MoveDirection(-ev.position.normalize_or_zero()),
-                Speed(speed),
-                HomingTarget,
-                Enemy { sprite_e },
-                Health(5),
+    let speed = rng.gen_range(ev.kind.base_speed()..(ev.kind.base_speed() * 1.5));
+    // let speed = rng.gen_range(ev.kind.base_speed()..(ev.kind.base_speed() * 1.5)) * 5.;
+
+    let (enemy_e, reused) = pool.spawn(&mut cmd, PoolKey::Enemy(ev.kind));
+    let pooled_sprite_e = reused
+        .then(|| reset_pooled_enemy(&mut cmd, enemy_e, &children_q, &barrel_q, &mut sprite_q))
+        .flatten();
+    let sprite_e = pooled_sprite_e
+        .unwrap_or_else(|| spawn_enemy_sprites(&mut cmd, enemy_e, ev.kind, &sprites));
+
+    let mut enemy_cmd = cmd.entity(enemy_e);
+    enemy_cmd.remove::<(Damping, Garbage)>().insert((
+        Name::new(name),
+        Transform::from_translation(ev.position.extend(0.1)).with_rotation(Quat::from_rotation_z(
+            offset.to_angle() + 90f32.to_radians(),
+        )),
+        Visibility::default(),
+        collider,
+        GameLayer::Enemy.layers(),
+        MoveDirection(-offset.normalize_or_zero()),
+        Impulse::default(),
+        Speed(speed),
+        HomingTarget,
+        Enemy {
+            kind: ev.kind,
+            sprite_e,
+            core_e: ev.core_e,
+        },
+        Health(health),
+        StateScoped(Screen::Game),
+    ));
+    match ev.kind {
+        EnemyKind::Shieldy => {
+            enemy_cmd.insert(Shielded);
+        }
+        EnemyKind::BangBang => {
+            enemy_cmd.insert((
                 StopNearCore(rng.gen_range((PADDLE_RADIUS * 2.0)..(PADDLE_RADIUS * 2.4))),
                 SpeedMultiplier::default(),
-                StateScoped(Screen::Game),
+            ));
+        }
+        _ => {}
+    }
+
+    if ev.garbage {
+        enemy_cmd.insert(Garbage);
+    }
+}
+
+/// Spawns the sprite of a new enemy along with its gun barrel and returns the sprite.
+fn spawn_enemy_sprites(
+    cmd: &mut Commands,
+    enemy_e: Entity,
+    kind: EnemyKind,
+    sprites: &SpriteAssets,
+) -> Entity {
+    let image = match kind {
+        EnemyKind::Creepinek => sprites.enemy_creepinek.clone_weak(),
+        EnemyKind::Shieldy => sprites.enemy_creepy_shield.clone_weak(),
+        EnemyKind::BigBoi => sprites.enemy_big_boi.clone_weak(),
+        EnemyKind::BangBang | EnemyKind::ShieldedBang => sprites.enemy_bang.clone(),
+    };
+    let sprite_e = cmd
+        .spawn(Sprite {
+            image,
+            color: COL_ENEMY,
+            ..default()
+        })
+        .id();
+    cmd.entity(enemy_e).add_child(sprite_e);
+
+    if kind == EnemyKind::BangBang {
+        let size = 50.;
+        let barrel_e = cmd
+            .spawn((
+                Sprite {
+                    image: sprites.enemy_bang_barrel.clone(),
+                    color: COL_ENEMY,
+                    ..default()
+                },
+                Transform::from_translation(Vec3::Y * (size + 10.)),
+                EnemyGunBarrel::Inactive,
             ))
-            .add_child(sprite_e)
-            .add_child(barrel_e);
+            .id();
+        cmd.entity(enemy_e).add_child(barrel_e);
+    }
+    sprite_e
+}
+
+/// Puts the children of a parked enemy back into their initial state and returns its sprite.
+fn reset_pooled_enemy(
+    cmd: &mut Commands,
+    enemy_e: Entity,
+    children_q: &Query<&Children>,
+    barrel_q: &Query<(), With<EnemyGunBarrel>>,
+    sprite_q: &mut Query<&mut Sprite>,
+) -> Option<Entity> {
+    let mut sprite_e = None;
+    for &child_e in children_q.get(enemy_e).ok()? {
+        // the hit flash might have been cut off by the despawn
+        if let Ok(mut sprite) = sprite_q.get_mut(child_e) {
+            sprite.color = COL_ENEMY;
         }
-        EnemyKind::ShieldedBang => todo!(),
+        cmd.entity(child_e).remove::<Animator<Sprite>>();
+        if barrel_q.contains(child_e) {
+            cmd.entity(child_e)
+                .remove::<Cooldown<EnemyGunBarrel>>()
+                .insert(EnemyGunBarrel::Inactive);
+            continue;
+        }
+        cmd.entity(child_e)
+            .remove::<(Animator<Transform>, DespawnOnTweenCompleted)>()
+            .insert(Transform::IDENTITY);
+        sprite_e = Some(child_e);
     }
+    sprite_e
 }
 
 // todo: extract to template
@@ -329,15 +484,20 @@ fn enemy_flash_on_hit(
 fn slow_down_near_core(
     mut stop_q: Query<(
         Entity,
+        &Enemy,
         &StopNearCore,
         &mut SpeedMultiplier,
         &GlobalTransform,
     )>,
+    core_q: Query<&GlobalTransform, With<Core>>,
     child_q: Query<&Children>,
     mut barrel_q: Query<&mut EnemyGunBarrel>,
 ) {
-    for (e, stop, mut speed_mult, t) in &mut stop_q {
-        let core_dist = t.translation().length();
+    for (e, enemy, stop, mut speed_mult, t) in &mut stop_q {
+        let Ok(core_t) = core_q.get(enemy.core_e) else {
+            continue;
+        };
+        let core_dist = t.translation().distance(core_t.translation());
         let prev_speed_mult = speed_mult.0;
         speed_mult.0 = inverse_lerp_clamped(stop.0, stop.0 + 50.0, core_dist).powf(1.7);
         let cutoff = 0.05;
@@ -363,7 +523,9 @@ fn despawn_enemy(
     mut ev_r: EventReader<DespawnEnemy>,
     mut cmd: Commands,
     enemy_q: Query<(&Enemy, &GlobalTransform)>,
+    core_q: Query<&GlobalTransform, With<Core>>,
     particles: Res<ParticleAssets>,
+    mut drop_rng: ResMut<DropRng>,
 ) -> Result {
     for ev in ev_r.read() {
         let (enemy, t) = enemy_q.get(ev.0)?;
@@ -372,12 +534,22 @@ fn despawn_enemy(
             get_relative_scale_anim(Vec2::ZERO.extend(1.), 150, Some(EaseFunction::BounceIn)),
             DespawnOnTweenCompleted::Entity(ev.0),
         ));
-        cmd.spawn((
-            particles.square_particle_spawner(),
-            ParticleEffectHandle(particles.enemy.clone_weak()),
-            Transform::from_translation(t.translation()),
-            OneShot::Despawn,
-        ));
+        cmd.spawn_particles(
+            PoolKey::SquareParticles,
+            (
+                particles.square_particle_spawner(),
+                ParticleEffectHandle(particles.enemy.clone_weak()),
+                Transform::from_translation(t.translation()),
+            ),
+        );
+        // enemies cleared near the core don't drop anything
+        let core_pos = core_q
+            .get(enemy.core_e)
+            .map(|t| t.translation())
+            .unwrap_or_default();
+        if t.translation().distance(core_pos) > PADDLE_RADIUS {
+            roll_pickup_drop(&mut cmd, &mut drop_rng.0, t.translation().truncate());
+        }
     }
     Ok(())
 }
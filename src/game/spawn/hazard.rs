@@ -0,0 +1,297 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    game::{arena::ArenaPart, collision::GameLayer},
+    screen::Screen,
+    theme::palette::{COL_BULLET, COL_ENEMY, COL_GEARS_DISABLED, COL_PADDLE_2, COL_PADDLE_TRACKS},
+};
+
+use super::level::Wall;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(spawn_hazard);
+}
+
+const HAZARD_WALL_THICKNESS: f32 = 12.;
+
+/// Hazard as written in an arena's level script.
+#[derive(Debug, Clone, Deserialize)]
+pub enum HazardDef {
+    /// Wall segment spinning around its center.
+    RotatingWall {
+        position: (f32, f32),
+        length: f32,
+        speed_deg: f32,
+        #[serde(default)]
+        rotation_deg: f32,
+    },
+    /// Pulls the ball and projectiles within the radius towards its center.
+    GravityWell {
+        position: (f32, f32),
+        radius: f32,
+        strength: f32,
+    },
+    /// Pair of portals, the ball entering one comes out of the other.
+    Portal {
+        a: (f32, f32),
+        b: (f32, f32),
+        radius: f32,
+    },
+    /// Raises the ball speed when crossed.
+    SpeedStrip {
+        position: (f32, f32),
+        size: (f32, f32),
+        #[serde(default)]
+        rotation_deg: f32,
+        speed_mult: f32,
+    },
+    /// Wall that breaks after enough ball or bullet hits.
+    Barrier {
+        position: (f32, f32),
+        size: (f32, f32),
+        #[serde(default)]
+        rotation_deg: f32,
+        health: u8,
+    },
+    /// Bank of targets dropping when hit, knocking all of them down awards points and ammo.
+    DropTargets {
+        targets: Vec<(f32, f32)>,
+        size: (f32, f32),
+        #[serde(default)]
+        rotation_deg: f32,
+        points: usize,
+        ammo: usize,
+    },
+    /// Awards points and ammo whenever the ball rolls through.
+    BonusLane {
+        position: (f32, f32),
+        size: (f32, f32),
+        #[serde(default)]
+        rotation_deg: f32,
+        points: usize,
+        ammo: usize,
+    },
+}
+
+#[derive(Event, Debug)]
+pub struct SpawnHazard(pub HazardDef);
+
+#[derive(Component, Debug)]
+pub struct Rotating {
+    /// Radians per second.
+    pub speed: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct GravityWell {
+    pub radius: f32,
+    pub strength: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct Portal {
+    pub exit: Vec2,
+}
+
+#[derive(Component, Debug)]
+pub struct SpeedStrip {
+    pub speed_mult: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct Barrier {
+    pub health: u8,
+}
+
+#[derive(Component, Debug)]
+pub struct DropTargetBank {
+    pub points: usize,
+    pub ammo: usize,
+    /// Raises the targets again once all of them are down.
+    pub reset_timer: Option<Timer>,
+}
+
+#[derive(Component, Debug)]
+pub struct DropTarget {
+    pub bank_e: Entity,
+    pub size: Vec2,
+    pub down: bool,
+}
+
+#[derive(Component, Debug)]
+pub struct BonusLane {
+    pub points: usize,
+    pub ammo: usize,
+}
+
+fn spawn_hazard(
+    trigger: Trigger<SpawnHazard>,
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let base = || (ArenaPart, StateScoped(Screen::Game));
+    let transform = |position: (f32, f32), rotation_deg: f32| {
+        Transform::from_translation(Vec2::from(position).extend(0.05))
+            .with_rotation(Quat::from_rotation_z(rotation_deg.to_radians()))
+    };
+
+    match trigger.0.clone() {
+        HazardDef::RotatingWall {
+            position,
+            length,
+            speed_deg,
+            rotation_deg,
+        } => {
+            cmd.spawn((
+                Name::new("rotating_wall"),
+                transform(position, rotation_deg),
+                Mesh2d(meshes.add(Rectangle::new(length, HAZARD_WALL_THICKNESS))),
+                MeshMaterial2d(materials.add(ColorMaterial::from_color(COL_PADDLE_TRACKS))),
+                Collider::segment(Vec2::X * -length / 2., Vec2::X * length / 2.),
+                GameLayer::Wall.layers(),
+                Wall,
+                Rotating {
+                    speed: speed_deg.to_radians(),
+                },
+                base(),
+            ));
+        }
+        HazardDef::GravityWell {
+            position,
+            radius,
+            strength,
+        } => {
+            cmd.spawn((
+                Name::new("gravity_well"),
+                transform(position, 0.),
+                Mesh2d(meshes.add(Annulus::new(radius - 4., radius))),
+                MeshMaterial2d(
+                    materials.add(ColorMaterial::from_color(COL_ENEMY.with_alpha(0.35))),
+                ),
+                GravityWell { radius, strength },
+                base(),
+            ));
+        }
+        HazardDef::Portal { a, b, radius } => {
+            let material = materials.add(ColorMaterial::from_color(COL_PADDLE_2.with_alpha(0.6)));
+            let mesh = meshes.add(Annulus::new(radius * 0.7, radius));
+            for (from, to) in [(a, b), (b, a)] {
+                cmd.spawn((
+                    Name::new("portal"),
+                    transform(from, 0.),
+                    Mesh2d(mesh.clone()),
+                    MeshMaterial2d(material.clone()),
+                    Collider::circle(radius),
+                    Sensor,
+                    GameLayer::Hazard.layers(),
+                    Portal {
+                        exit: Vec2::from(to),
+                    },
+                    base(),
+                ));
+            }
+        }
+        HazardDef::SpeedStrip {
+            position,
+            size,
+            rotation_deg,
+            speed_mult,
+        } => {
+            cmd.spawn((
+                Name::new("speed_strip"),
+                transform(position, rotation_deg),
+                Mesh2d(meshes.add(Rectangle::new(size.0, size.1))),
+                MeshMaterial2d(
+                    materials.add(ColorMaterial::from_color(COL_BULLET.with_alpha(0.3))),
+                ),
+                Collider::rectangle(size.0, size.1),
+                Sensor,
+                GameLayer::Hazard.layers(),
+                SpeedStrip { speed_mult },
+                base(),
+            ));
+        }
+        HazardDef::Barrier {
+            position,
+            size,
+            rotation_deg,
+            health,
+        } => {
+            cmd.spawn((
+                Name::new("barrier"),
+                transform(position, rotation_deg),
+                Mesh2d(meshes.add(Rectangle::new(size.0, size.1))),
+                MeshMaterial2d(materials.add(ColorMaterial::from_color(COL_GEARS_DISABLED))),
+                Collider::rectangle(size.0, size.1),
+                GameLayer::Wall.layers(),
+                Wall,
+                Barrier {
+                    health: health.max(1),
+                },
+                base(),
+            ));
+        }
+        HazardDef::DropTargets {
+            targets,
+            size,
+            rotation_deg,
+            points,
+            ammo,
+        } => {
+            let bank_e = cmd
+                .spawn((
+                    Name::new("drop_target_bank"),
+                    DropTargetBank {
+                        points,
+                        ammo,
+                        reset_timer: None,
+                    },
+                    base(),
+                ))
+                .id();
+            let mesh = meshes.add(Rectangle::new(size.0, size.1));
+            let material = materials.add(ColorMaterial::from_color(COL_BULLET));
+            for position in targets {
+                cmd.spawn((
+                    Name::new("drop_target"),
+                    transform(position, rotation_deg),
+                    Mesh2d(mesh.clone()),
+                    MeshMaterial2d(material.clone()),
+                    Collider::rectangle(size.0, size.1),
+                    GameLayer::Wall.layers(),
+                    Wall,
+                    DropTarget {
+                        bank_e,
+                        size: Vec2::from(size),
+                        down: false,
+                    },
+                    base(),
+                ));
+            }
+        }
+        HazardDef::BonusLane {
+            position,
+            size,
+            rotation_deg,
+            points,
+            ammo,
+        } => {
+            cmd.spawn((
+                Name::new("bonus_lane"),
+                transform(position, rotation_deg),
+                Mesh2d(meshes.add(Rectangle::new(size.0, size.1))),
+                MeshMaterial2d(
+                    materials.add(ColorMaterial::from_color(COL_PADDLE_2.with_alpha(0.25))),
+                ),
+                Collider::rectangle(size.0, size.1),
+                Sensor,
+                GameLayer::Hazard.layers(),
+                BonusLane { points, ammo },
+                base(),
+            ));
+        }
+    }
+}
@@ -6,13 +6,16 @@ use bevy_enoki::ParticleEffectHandle;
 use bevy_tweening::Animator;
 
 use crate::{
-    GAME_SIZE,
     game::{
+        arena::{Arena, ArenaAssets, ArenaKind, ArenaPart, ObstacleKind, ObstacleShape},
         assets::{ParticleAssets, SpriteAssets},
-        tween::{delay_tween, get_relative_scale_tween},
+        collision::GameLayer,
+        mode::RunRules,
+        player::{Multiplayer, Player},
+        tween::{RealTimeTween, delay_tween, get_relative_scale_tween},
     },
     screen::Screen,
-    theme::palette::{COL_AMMO_BG, COL_AMMO_FILL, COL_AMMO_OUT, COL_GEARS},
+    theme::palette::{COL_AMMO_BG, COL_AMMO_OUT, COL_BALL, COL_GEARS, COL_PADDLE_TRACKS},
 };
 
 use super::{
@@ -22,16 +25,22 @@ use super::{
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(spawn_level)
+        .add_observer(spawn_arena)
         .add_systems(Update, (add_ball_to_paddle,));
 }
 
 pub const CORE_RADIUS: f32 = 90.0;
 pub const AMMO_FILL_RADIUS: f32 = 34.0;
 pub const GEAR_COUNT: u8 = 8;
+const WALL_THICKNESS: f32 = 12.;
 
 #[derive(Event, Debug)]
 pub struct SpawnLevel;
 
+/// Spawns the walls and obstacles of an arena.
+#[derive(Event, Debug)]
+pub struct SpawnArena(pub ArenaKind);
+
 #[derive(Component, Debug)]
 pub struct Core {
     pub gear_entities: Vec<(Entity, bool)>,
@@ -44,6 +53,10 @@ pub struct Health(pub u8);
 #[derive(Component, Debug)]
 pub struct Wall;
 
+/// Wall kicking the ball away faster instead of slowing it down.
+#[derive(Component, Debug)]
+pub struct Bumper;
+
 #[derive(Component, Debug)]
 pub struct RotateWithPaddle {
     pub invert: bool,
@@ -64,131 +77,226 @@ fn spawn_level(
     particles: Res<ParticleAssets>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    multiplayer: Res<Multiplayer>,
+    rules: Res<RunRules>,
 ) {
-    // todo: make the gears & center icons lighter/improve contrast with ball
-    let cog_entity_ids: Vec<_> = (0..GEAR_COUNT)
-        .map(|i| {
-            let rot = Rot2::degrees((360f32 / GEAR_COUNT as f32) * i as f32 + 90.);
-            let angle = rot.as_radians() + 18f32.to_radians();
-            cmd.spawn((
-                Name::new("small_gear"),
-                Sprite {
-                    image: sprites.gear_small.clone_weak(),
-                    color: COL_GEARS,
-                    ..default()
-                },
-                Transform::from_translation(((rot * Vec2::X) * 71.).extend(0.1))
-                    .with_rotation(Quat::from_rotation_z(angle))
-                    .with_scale(Vec2::ZERO.extend(1.)),
-                RotateWithPaddle {
-                    invert: i % 2 == 0,
-                    offset: Rot2::radians(angle),
-                    multiplier: 1.0,
-                },
-                Animator::new(delay_tween(
-                    get_relative_scale_tween(Vec3::ONE, 400, Some(EaseFunction::BackOut)),
-                    350 + i as u64 * 100,
-                )),
+    for (core_pos, players) in multiplayer.cores() {
+        // todo: make the gears & center icons lighter/improve contrast with ball
+        let cog_entity_ids: Vec<_> = (0..rules.gears)
+            .map(|i| {
+                let rot = Rot2::degrees((360f32 / GEAR_COUNT as f32) * i as f32 + 90.);
+                let angle = rot.as_radians() + 18f32.to_radians();
+                cmd.spawn((
+                    Name::new("small_gear"),
+                    Sprite {
+                        image: sprites.gear_small.clone_weak(),
+                        color: COL_GEARS,
+                        ..default()
+                    },
+                    Transform::from_translation(((rot * Vec2::X) * 71.).extend(0.1))
+                        .with_rotation(Quat::from_rotation_z(angle))
+                        .with_scale(Vec2::ZERO.extend(1.)),
+                    RotateWithPaddle {
+                        invert: i % 2 == 0,
+                        offset: Rot2::radians(angle),
+                        multiplier: 1.0,
+                    },
+                    Animator::new(delay_tween(
+                        get_relative_scale_tween(Vec3::ONE, 400, Some(EaseFunction::BackOut)),
+                        350 + i as u64 * 100,
+                    )),
+                ))
+                .id()
+            })
+            .collect();
+
+        let clear_mesh_id = cmd
+            .spawn((
+                Name::new("clear_flash"),
+                Mesh2d(meshes.add(Circle::new(PADDLE_RADIUS))),
+                MeshMaterial2d(materials.add(ColorMaterial::from_color(Color::NONE))),
+                Transform::from_translation(core_pos.extend(0.001)),
             ))
-            .id()
-        })
-        .collect();
-
-    let clear_mesh_id = cmd
-        .spawn((
-            Name::new("clear_flash"),
-            Mesh2d(meshes.add(Circle::new(PADDLE_RADIUS))),
-            MeshMaterial2d(materials.add(ColorMaterial::from_color(Color::NONE))),
-            Transform::from_translation(Vec3::Z * 0.001),
-        ))
-        .id();
-
-    cmd.spawn((
-        Name::new("core"),
-        Transform::default(),
-        Visibility::default(),
-        Collider::circle(CORE_RADIUS),
-        CollidingEntities::default(),
-        RigidBody::Static,
-        Core {
-            gear_entities: cog_entity_ids
-                .iter()
-                .cloned()
-                .map(|e| (e, true))
-                .rev()
-                .collect(),
-            clear_mesh_e: clear_mesh_id,
-        },
-        Health(GEAR_COUNT),
-        StateScoped(Screen::Game),
-    ))
-    .add_children(&cog_entity_ids)
-    .with_children(|b| {
-        b.spawn((
-            Transform::from_scale(Vec2::ZERO.extend(1.)),
-            Visibility::default(),
-            AmmoUi,
-            Animator::new(delay_tween(
-                get_relative_scale_tween(Vec3::ONE, 400, Some(EaseFunction::BackOut)),
-                300,
-            )),
-        ))
-        .with_children(|b| {
-            // ammo UI
-            b.spawn((
-                Name::new("ammo_sprite"),
-                Sprite {
-                    image: sprites.ammo_icon.clone_weak(),
-                    color: COL_AMMO_BG,
-                    ..default()
+            .id();
+
+        let core_e = cmd
+            .spawn((
+                Name::new("core"),
+                Transform::from_translation(core_pos.extend(0.)),
+                Visibility::default(),
+                Collider::circle(CORE_RADIUS),
+                GameLayer::Core.layers(),
+                CollisionEventsEnabled,
+                RigidBody::Static,
+                Core {
+                    gear_entities: cog_entity_ids
+                        .iter()
+                        .cloned()
+                        .map(|e| (e, true))
+                        .rev()
+                        .collect(),
+                    clear_mesh_e: clear_mesh_id,
                 },
-                Transform::from_translation(Vec3::Z * 0.3),
-            ));
+                Health(rules.gears),
+                StateScoped(Screen::Game),
+            ))
+            .add_children(&cog_entity_ids)
+            .with_children(|b| {
+                // co-op players share the core and get smaller ammo UIs side by side
+                let (ammo_ui_scale, ammo_ui_offset) = if players.len() > 1 {
+                    (0.55, 24.)
+                } else {
+                    (1., 0.)
+                };
+                for &player in &players {
+                    let sign = if player.0 == 0 { -1. } else { 1. };
+                    b.spawn((
+                        Name::new("ammo_ui_slot"),
+                        Transform::from_translation(Vec3::X * ammo_ui_offset * sign)
+                            .with_scale(Vec2::splat(ammo_ui_scale).extend(1.)),
+                        Visibility::default(),
+                    ))
+                    .with_children(|b| {
+                        b.spawn((
+                            Transform::from_scale(Vec2::ZERO.extend(1.)),
+                            Visibility::default(),
+                            AmmoUi,
+                            player,
+                            RealTimeTween,
+                            Animator::new(delay_tween(
+                                get_relative_scale_tween(
+                                    Vec3::ONE,
+                                    400,
+                                    Some(EaseFunction::BackOut),
+                                ),
+                                300,
+                            )),
+                        ))
+                        .with_children(|b| {
+                            // ammo UI
+                            b.spawn((
+                                Name::new("ammo_sprite"),
+                                Sprite {
+                                    image: sprites.ammo_icon.clone_weak(),
+                                    color: COL_AMMO_BG,
+                                    ..default()
+                                },
+                                Transform::from_translation(Vec3::Z * 0.3),
+                            ));
 
-            b.spawn((
-                Name::new("ammo_fill"),
-                AmmoFill,
-                MeshMaterial2d(materials.add(ColorMaterial::from_color(COL_AMMO_FILL))),
-                Transform::from_translation(Vec3::Z * 0.2)
-                    .with_rotation(Quat::from_rotation_z(180f32.to_radians())),
-            ));
+                            b.spawn((
+                                Name::new("ammo_fill"),
+                                AmmoFill,
+                                player,
+                                MeshMaterial2d(
+                                    materials.add(ColorMaterial::from_color(player.ammo_color())),
+                                ),
+                                Transform::from_translation(Vec3::Z * 0.2)
+                                    .with_rotation(Quat::from_rotation_z(180f32.to_radians())),
+                            ));
 
-            b.spawn((
-                Name::new("ammo_bg"),
-                Mesh2d(meshes.add(Circle::new(AMMO_FILL_RADIUS + 2.))),
-                MeshMaterial2d(materials.add(ColorMaterial::from_color(COL_AMMO_OUT))),
-                Transform::from_translation(Vec3::Z * 0.1),
-            ));
-        });
+                            b.spawn((
+                                Name::new("ammo_bg"),
+                                Mesh2d(meshes.add(Circle::new(AMMO_FILL_RADIUS + 2.))),
+                                MeshMaterial2d(
+                                    materials.add(ColorMaterial::from_color(COL_AMMO_OUT)),
+                                ),
+                                Transform::from_translation(Vec3::Z * 0.1),
+                            ));
+                        });
+                    });
+                }
 
-        //particles
-        b.spawn((
-            particles.circle_particle_spawner(),
-            ParticleEffectHandle(particles.core.clone_weak()),
-        ));
-    });
+                //particles
+                b.spawn((
+                    particles.circle_particle_spawner(),
+                    ParticleEffectHandle(particles.core.clone_weak()),
+                ));
+            })
+            .id();
+
+        for player in players {
+            cmd.trigger(SpawnPaddle {
+                player,
+                core_e,
+                position: core_pos,
+            });
+        }
+    }
 
-    cmd.trigger(SpawnPaddle);
+    cmd.trigger(SpawnArena(rules.arena.kind(0)));
+}
+
+fn spawn_arena(
+    trigger: Trigger<SpawnArena>,
+    mut cmd: Commands,
+    arena_assets: Res<ArenaAssets>,
+    arenas: Res<Assets<Arena>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) -> Result {
+    let arena = arenas
+        .get(arena_assets.get(trigger.0))
+        .ok_or("arena isn't loaded")?;
 
-    let half_size = GAME_SIZE / 2.;
+    let wall_material = materials.add(ColorMaterial::from_color(COL_PADDLE_TRACKS));
+    for (a, b) in arena.segments() {
+        let length = a.distance(b);
+        let mut wall = cmd.spawn((
+            Name::new("wall"),
+            Transform::from_translation(a.midpoint(b).extend(0.))
+                .with_rotation(Quat::from_rotation_z((b - a).to_angle())),
+            Visibility::default(),
+            Collider::segment(Vec2::X * -length / 2., Vec2::X * length / 2.),
+            GameLayer::Wall.layers(),
+            Wall,
+            ArenaPart,
+            StateScoped(Screen::Game),
+        ));
+        if !arena.hidden_walls {
+            wall.insert((
+                Mesh2d(meshes.add(Rectangle::new(length + WALL_THICKNESS, WALL_THICKNESS))),
+                MeshMaterial2d(wall_material.clone()),
+            ));
+        }
+    }
 
-    for (a, b) in [
-        (Vec2::new(-1., 1.), Vec2::ONE),
-        (Vec2::ONE, Vec2::new(1., -1.)),
-        (Vec2::new(1., -1.), Vec2::NEG_ONE),
-        (Vec2::NEG_ONE, Vec2::new(-1., 1.)),
-    ] {
-        cmd.spawn((
-            // TransformBundle::default(),
-            Collider::segment(a * -half_size, b * half_size),
+    for obstacle in &arena.obstacles {
+        let (collider, mesh) = match obstacle.shape {
+            ObstacleShape::Circle(radius) => {
+                (Collider::circle(radius), meshes.add(Circle::new(radius)))
+            }
+            ObstacleShape::Rect(width, height) => (
+                Collider::rectangle(width, height),
+                meshes.add(Rectangle::new(width, height)),
+            ),
+        };
+        let color = match obstacle.kind {
+            ObstacleKind::Pillar => COL_GEARS,
+            ObstacleKind::Bumper => COL_BALL,
+        };
+        let mut obstacle_cmd = cmd.spawn((
+            Name::new("obstacle"),
+            Transform::from_translation(Vec2::from(obstacle.position).extend(0.))
+                .with_rotation(Quat::from_rotation_z(obstacle.rotation_deg.to_radians())),
+            Mesh2d(mesh),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(color))),
+            collider,
+            GameLayer::Wall.layers(),
             Wall,
+            ArenaPart,
             StateScoped(Screen::Game),
         ));
+        if obstacle.kind == ObstacleKind::Bumper {
+            obstacle_cmd.insert(Bumper);
+        }
     }
+    Ok(())
 }
 
-fn add_ball_to_paddle(paddle_q: Query<Entity, Added<Paddle>>, mut cmd: Commands) {
-    for paddle_e in &paddle_q {
+/// There's only a single ball, additional players have to fight for it.
+fn add_ball_to_paddle(paddle_q: Query<(Entity, &Player), Added<Paddle>>, mut cmd: Commands) {
+    for (paddle_e, _) in paddle_q.iter().filter(|(_, p)| p.0 == 0) {
         cmd.trigger(SpawnBall {
             paddle_e,
             tween_delay_ms: 1400,
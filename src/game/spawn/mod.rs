@@ -7,8 +7,10 @@ use bevy::prelude::*;
 pub mod ball;
 pub mod despawn;
 pub mod enemy;
+pub mod hazard;
 pub mod level;
 pub mod paddle;
+pub mod pickup;
 pub mod projectile;
 
 pub(super) fn plugin(app: &mut App) {
@@ -17,7 +19,9 @@ pub(super) fn plugin(app: &mut App) {
         paddle::plugin,
         ball::plugin,
         enemy::plugin,
+        hazard::plugin,
         projectile::plugin,
+        pickup::plugin,
         despawn::plugin,
     ));
 }
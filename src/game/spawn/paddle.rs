@@ -1,19 +1,27 @@
-use std::time::Duration;
+use std::f32::consts::PI;
 
 use avian2d::prelude::*;
 use bevy::{prelude::*, render::mesh::AnnulusMeshBuilder};
 use bevy_tweening::Animator;
+use leafwing_input_manager::prelude::*;
 
 use crate::{
     ext::TransExt,
     game::{
         assets::SpriteAssets,
+        collision::GameLayer,
+        gesture::GestureRecognizer,
+        input::{AimDirection, PlayerAction},
+        mode::RunRules,
         movement::AccumulatedRotation,
+        player::{Multiplayer, Player},
+        progression::Progression,
         tween::{delay_tween, get_relative_scale_tween},
+        weapon::Weapon,
     },
     screen::Screen,
     theme::palette::{
-        COL_PADDLE, COL_PADDLE_CAPTURE, COL_PADDLE_CAPTURED, COL_PADDLE_REFLECT, COL_PADDLE_TRACKS,
+        COL_PADDLE_CAPTURE, COL_PADDLE_CAPTURED, COL_PADDLE_REFLECT, COL_PADDLE_TRACKS,
     },
 };
 
@@ -28,13 +36,20 @@ pub const PADDLE_HEIGHT: f32 = 120.0;
 pub const PADDLE_COLL_HEIGHT: f32 = PADDLE_HEIGHT + 20.;
 
 #[derive(Event, Debug)]
-pub struct SpawnPaddle;
+pub struct SpawnPaddle {
+    pub player: Player,
+    pub core_e: Entity,
+    /// Position of the guarded core.
+    pub position: Vec2,
+}
 
 #[derive(Component, Debug)]
 pub struct Paddle {
     pub sprite_e: Entity,
     pub barrel_e: Entity,
     pub reflect_e: Entity,
+    /// Core guarded by this paddle.
+    pub core_e: Entity,
 }
 
 #[derive(Component, Debug)]
@@ -59,35 +74,9 @@ impl PaddleMode {
 
 #[derive(Component, Debug)]
 pub struct PaddleRotation {
-    pub cw_start: f32,
-    pub ccw_start: f32,
-    pub timer: Timer,
-    pub prev_rot: f32,
     pub paddle_e: Entity,
 }
 
-impl PaddleRotation {
-    fn new(paddle_e: Entity) -> Self {
-        Self {
-            cw_start: 0.,
-            ccw_start: 0.,
-            timer: Timer::new(Duration::from_millis(50), TimerMode::Once),
-            prev_rot: 0.,
-            paddle_e,
-        }
-    }
-}
-
-impl PaddleRotation {
-    pub fn reset(&mut self, rotation: f32) {
-        self.cw_start = rotation;
-        self.ccw_start = rotation;
-        self.prev_rot = rotation;
-        self.timer.reset();
-        self.timer.unpause();
-    }
-}
-
 #[derive(Component, Debug)]
 pub struct PaddleAmmo {
     ammo: usize,
@@ -103,28 +92,50 @@ impl PaddleAmmo {
         self.ammo = ((self.ammo as isize + delta) as usize).clamp(0, self.capacity);
     }
 
+    pub fn refill(&mut self) {
+        self.ammo = self.capacity;
+    }
+
     pub fn factor(&self) -> f32 {
         self.ammo as f32 / self.capacity as f32
     }
 }
 
 fn spawn_paddle(
-    _trigger: Trigger<SpawnPaddle>,
+    trigger: Trigger<SpawnPaddle>,
     mut cmd: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     sprites: Res<SpriteAssets>,
+    multiplayer: Res<Multiplayer>,
+    rules: Res<RunRules>,
+    progression: Res<Progression>,
 ) {
+    let ev = trigger.event();
+    let player = ev.player;
+    let radius = multiplayer.rail_radius(player);
+    let loadout = rules.loadout.then_some(&*progression);
+    // other players keep their colors so they can be told apart
+    let color = loadout
+        .and_then(Progression::skin)
+        .filter(|_| player.0 == 0)
+        .map_or(player.color(), |skin| skin.color());
+    // players sharing a rail spawn the rail only once
+    let rail_count = if *multiplayer == Multiplayer::Coop && player.0 > 0 {
+        0
+    } else {
+        2
+    };
+
     // rails/paddle radius
-    for (i, offset) in [-10., 15.].into_iter().enumerate() {
-        let annulus_builder =
-            AnnulusMeshBuilder::new(PADDLE_RADIUS + offset, PADDLE_RADIUS + offset + 10., 128);
+    for (i, offset) in [-10., 15.].into_iter().take(rail_count).enumerate() {
+        let annulus_builder = AnnulusMeshBuilder::new(radius + offset, radius + offset + 10., 128);
         annulus_builder.build();
         cmd.spawn((
             Name::new("rail"),
             Mesh2d(meshes.add(annulus_builder.build())),
             MeshMaterial2d(materials.add(ColorMaterial::from_color(COL_PADDLE_TRACKS))),
-            Transform::zero_scale_2d(),
+            Transform::zero_scale_2d().with_translation(ev.position.extend(0.)),
             Animator::new(delay_tween(
                 get_relative_scale_tween(Vec3::ONE, 600, Some(EaseFunction::BackOut)),
                 950 + i as u64 * 150,
@@ -140,7 +151,7 @@ fn spawn_paddle(
                 Name::new("barrel"),
                 Sprite {
                     image: sprites.paddle_barrel.clone_weak(),
-                    color: COL_PADDLE,
+                    color,
                     ..default()
                 },
                 Transform::from_xyz(0., 55., 0.),
@@ -167,7 +178,7 @@ fn spawn_paddle(
                 Name::new("base_sprite"),
                 Sprite {
                     image: sprites.paddle_base.clone_weak(),
-                    color: COL_PADDLE,
+                    color,
                     ..default()
                 },
                 Transform::from_xyz(7., 0., 0.)
@@ -185,7 +196,7 @@ fn spawn_paddle(
                         Name::new("wheel"),
                         Sprite {
                             image: sprites.paddle_wheel.clone_weak(),
-                            color: COL_PADDLE,
+                            color,
                             ..default()
                         },
                         Transform::from_xyz(98. * sign, -16., 0.),
@@ -204,29 +215,42 @@ fn spawn_paddle(
     let paddle_e = cmd
         .spawn((
             Name::new("paddle"),
-            Transform::from_xyz(PADDLE_RADIUS, 0.0, 1.0),
+            Transform::from_xyz(radius, 0.0, 1.0),
             Visibility::default(),
             Collider::capsule(23.0, PADDLE_COLL_HEIGHT),
+            GameLayer::Paddle.layers(),
             Paddle {
                 sprite_e,
                 barrel_e,
                 reflect_e,
+                core_e: ev.core_e,
             },
             PaddleMode::Reflect,
             PaddleAmmo {
                 capacity: 55,
-                ammo: 0,
+                ammo: loadout.map_or(0, Progression::starting_ammo),
+            },
+            Weapon {
+                kind: loadout.and_then(Progression::weapon).unwrap_or_default(),
+                ..default()
             },
+            player,
+            PlayerAction::input_map(player.0 == 0, true),
+            ActionState::<PlayerAction>::default(),
+            AimDirection::default(),
         ))
         .add_child(sprite_e)
         .id();
 
     cmd.spawn((
         Name::new("paddle_rotation"),
-        Transform::default(),
+        // players start on opposite sides
+        Transform::from_translation(ev.position.extend(0.))
+            .with_rotation(Quat::from_rotation_z(PI * player.0 as f32)),
         Visibility::default(),
-        PaddleRotation::new(paddle_e),
+        PaddleRotation { paddle_e },
         AccumulatedRotation::default(),
+        GestureRecognizer::default(),
         StateScoped(Screen::Game),
     ))
     .add_child(paddle_e);
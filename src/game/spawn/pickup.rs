@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy_tweening::Animator;
+use rand::{distributions::WeightedIndex, prelude::*};
+
+use crate::{
+    game::{
+        assets::SpriteAssets,
+        movement::{MoveDirection, Speed},
+        tween::{delay_tween, get_relative_scale_tween},
+    },
+    screen::Screen,
+    theme::palette::{COL_BALL, COL_BULLET, COL_GEARS, COL_PICKUP_SHIELD},
+};
+
+use super::level::Core;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(spawn_pickup);
+}
+
+pub const PICKUP_RADIUS: f32 = 35.;
+pub const PICKUP_DROP_CHANCE: f64 = 0.12;
+const PICKUP_SPEED: f32 = 60.;
+
+#[derive(Event, Debug)]
+pub struct SpawnPickup {
+    pub kind: PickupKind,
+    pub position: Vec2,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    Ammo,
+    GearRepair,
+    MultiShot,
+    BigBall,
+    CoreShield,
+}
+
+impl PickupKind {
+    pub const ALL: [PickupKind; 5] = [
+        PickupKind::Ammo,
+        PickupKind::GearRepair,
+        PickupKind::MultiShot,
+        PickupKind::BigBall,
+        PickupKind::CoreShield,
+    ];
+
+    fn weight(&self) -> u32 {
+        match self {
+            PickupKind::Ammo => 4,
+            PickupKind::GearRepair => 1,
+            PickupKind::MultiShot => 2,
+            PickupKind::BigBall => 2,
+            PickupKind::CoreShield => 1,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PickupKind::Ammo | PickupKind::MultiShot => COL_BULLET,
+            PickupKind::GearRepair => COL_GEARS,
+            PickupKind::BigBall => COL_BALL,
+            PickupKind::CoreShield => COL_PICKUP_SHIELD,
+        }
+    }
+
+    pub fn image(&self, sprites: &SpriteAssets) -> Handle<Image> {
+        match self {
+            PickupKind::Ammo => sprites.ammo_icon.clone_weak(),
+            PickupKind::GearRepair => sprites.gear_small.clone_weak(),
+            PickupKind::MultiShot => sprites.bullet.clone_weak(),
+            PickupKind::BigBall => sprites.ball.clone_weak(),
+            PickupKind::CoreShield => sprites.transition_circle.clone_weak(),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PickupKind::Ammo => "AMMO",
+            PickupKind::GearRepair => "REPAIR",
+            PickupKind::MultiShot => "MULTI-SHOT",
+            PickupKind::BigBall => "BIG SAW",
+            PickupKind::CoreShield => "SHIELD",
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Pickup {
+    pub kind: PickupKind,
+}
+
+/// Spawn a random pickup at the given position with a [`PICKUP_DROP_CHANCE`].
+pub fn roll_pickup_drop(cmd: &mut Commands, rng: &mut impl Rng, position: Vec2) {
+    if !rng.gen_bool(PICKUP_DROP_CHANCE) {
+        return;
+    }
+
+    let weights = WeightedIndex::new(PickupKind::ALL.iter().map(PickupKind::weight))
+        .expect("Create weighted index");
+    cmd.trigger(SpawnPickup {
+        kind: PickupKind::ALL[weights.sample(rng)],
+        position,
+    });
+}
+
+fn spawn_pickup(
+    trigger: Trigger<SpawnPickup>,
+    mut cmd: Commands,
+    sprites: Res<SpriteAssets>,
+    core_q: Query<&GlobalTransform, With<Core>>,
+) {
+    let ev = trigger.event();
+    // drift towards the nearest core
+    let core_pos = core_q
+        .iter()
+        .map(|t| t.translation().truncate())
+        .min_by(|a, b| {
+            a.distance_squared(ev.position)
+                .total_cmp(&b.distance_squared(ev.position))
+        })
+        .unwrap_or_default();
+    cmd.spawn((
+        Name::new("pickup"),
+        Sprite {
+            image: ev.kind.image(&sprites),
+            color: ev.kind.color(),
+            custom_size: Some(Vec2::splat(PICKUP_RADIUS * 2.)),
+            ..default()
+        },
+        Transform::from_translation(ev.position.extend(0.2)).with_scale(Vec2::ZERO.extend(1.)),
+        MoveDirection((core_pos - ev.position).normalize_or_zero()),
+        Speed(PICKUP_SPEED),
+        Pickup { kind: ev.kind },
+        Animator::new(delay_tween(
+            get_relative_scale_tween(Vec3::ONE, 300, Some(EaseFunction::BackOut)),
+            100,
+        )),
+        StateScoped(Screen::Game),
+    ));
+}
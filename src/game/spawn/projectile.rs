@@ -1,12 +1,16 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
+use bevy_tweening::Animator;
 use rand::thread_rng;
 
 use crate::{
     ext::{RandExt, Vec2Ext},
     game::{
         assets::SpriteAssets,
-        movement::{Damping, MoveDirection, Speed},
+        collision::GameLayer,
+        movement::{Damping, Impulse, MoveDirection, Speed},
+        pool::{EntityPool, PoolKey},
+        tween::DespawnOnTweenCompleted,
     },
     screen::Screen,
     theme::palette::{COL_BULLET, COL_ENEMY_PROJECTILE},
@@ -24,6 +28,11 @@ pub struct SpawnProjectile {
     pub dir: Dir2,
     pub target: ProjectileTarget,
     pub max_accuracy_spread: f32,
+    pub speed: f32,
+    pub scale: Vec2,
+    pub damage: u8,
+    pub pierce: u8,
+    pub impulse: f32,
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,64 +41,116 @@ pub enum ProjectileTarget {
     Core,
 }
 
+impl ProjectileTarget {
+    pub fn layer(self) -> GameLayer {
+        match self {
+            ProjectileTarget::Enemy => GameLayer::PlayerProjectile,
+            ProjectileTarget::Core => GameLayer::EnemyProjectile,
+        }
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct Projectile {
     pub size: Vec2,
     pub sprite_e: Entity,
     pub target: ProjectileTarget,
+    pub damage: u8,
+    /// Knockback applied to hit enemies.
+    pub impulse: f32,
+    /// How many more enemies the projectile can pass through.
+    pub pierce: u8,
+    /// Enemies that have already been hit by a piercing projectile.
+    pub hit_entities: Vec<Entity>,
+    /// Enemy projectile sent back by a parry.
+    pub parried: bool,
 }
 
 fn spawn_projectile(
     trigger: Trigger<SpawnProjectile>,
     mut cmd: Commands,
     sprites: Res<SpriteAssets>,
+    children_q: Query<&Children>,
+    mut pool: ResMut<EntityPool>,
 ) {
     let mut rng = thread_rng();
     let ev = trigger.event();
-    let x = 16.;
-    let y = 30.;
+    let x = 16. * ev.scale.x;
+    let y = 30. * ev.scale.y;
     let dir_spread = rng.rotation_range_degrees(ev.max_accuracy_spread);
     let dir = dir_spread * ev.dir;
     let targets_enemy = ev.target == ProjectileTarget::Enemy;
-    let sprite_e = cmd
-        .spawn((
-            Sprite {
-                image: if targets_enemy {
-                    sprites.bullet.clone_weak()
-                } else {
-                    sprites.enemy_projectile.clone_weak()
-                },
-                color: if targets_enemy {
-                    COL_BULLET
-                } else {
-                    COL_ENEMY_PROJECTILE
-                },
-                ..default()
+    let sprite = (
+        Sprite {
+            image: if targets_enemy {
+                sprites.bullet.clone_weak()
+            } else {
+                sprites.enemy_projectile.clone_weak()
+            },
+            color: if targets_enemy {
+                COL_BULLET
+            } else {
+                COL_ENEMY_PROJECTILE
             },
-            Transform::from_rotation(Quat::from_rotation_z(180f32.to_radians())),
-        ))
-        .id();
-    cmd.spawn((
+            ..default()
+        },
+        Transform::from_rotation(Quat::from_rotation_z(180f32.to_radians()))
+            .with_scale(ev.scale.extend(1.)),
+    );
+
+    let (projectile_e, reused) = pool.spawn(&mut cmd, PoolKey::Projectile);
+    let pooled_sprite_e = reused
+        .then(|| children_q.get(projectile_e).ok()?.first().copied())
+        .flatten();
+    let sprite_e = match pooled_sprite_e {
+        Some(sprite_e) => {
+            // drop the shrinking tween of the previous hit
+            cmd.entity(sprite_e)
+                .remove::<(
+                    Animator<Transform>,
+                    Animator<Sprite>,
+                    DespawnOnTweenCompleted,
+                )>()
+                .insert(sprite);
+            sprite_e
+        }
+        None => {
+            let sprite_e = cmd.spawn(sprite).id();
+            cmd.entity(projectile_e).add_child(sprite_e);
+            sprite_e
+        }
+    };
+
+    cmd.entity(projectile_e).insert((
         Name::new("Projectile"),
         Transform::from_translation(ev.position.extend(0.1))
             .with_rotation(dir.rotate(Vec2::Y).to_quat()),
         Visibility::default(),
         RigidBody::Kinematic,
+        ev.target.layer().layers(),
+        SweptCcd::default(),
+        CollisionEventsEnabled,
+        TransformInterpolation,
         if targets_enemy {
             Collider::rectangle(x, y)
         } else {
-            Collider::circle(25.)
+            Collider::circle(25. * ev.scale.max_element())
         },
         MoveDirection(dir.as_vec2()),
-        Speed(if targets_enemy { 1600. } else { 250. }),
+        Impulse::default(),
+        Speed(ev.speed),
         Damping(if targets_enemy { 0.8 } else { 0.1 }),
         Projectile {
             target: ev.target,
             size: Vec2::new(x, y),
             sprite_e,
+            damage: ev.damage,
+            impulse: ev.impulse,
+            pierce: ev.pierce,
+            hit_entities: Vec::new(),
+            parried: false,
         },
         DespawnOutOfBounds,
         StateScoped(Screen::Game),
-    ))
-    .add_child(sprite_e);
+    ));
 }
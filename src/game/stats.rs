@@ -0,0 +1,149 @@
+//! Stats of the current run, shown on the game over screen.
+
+use bevy::prelude::*;
+
+use crate::screen::{Screen, in_game_state};
+
+use super::{
+    ball::BallCaptured,
+    core::{CoreDamaged, CoreDestroyed, DamageSource},
+    gesture::{Gesture, GesturePerformed},
+    gun::ShotHit,
+    mode::ModeClock,
+    movement::Speed,
+    spawn::{
+        ball::Ball,
+        enemy::{EnemyKilled, EnemyKind, KillSource},
+        paddle::PaddleAmmo,
+        projectile::{Projectile, ProjectileTarget},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RunStats>()
+        .add_observer(end_stats_on_core_destroyed)
+        .add_systems(OnEnter(Screen::Game), reset_run_stats)
+        .add_systems(Update, record_run_stats.run_if(in_game_state));
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct RunStats {
+    /// The run is over, nothing else is recorded.
+    pub ended: bool,
+    pub survival_s: f32,
+    /// Indexed by the position in [`EnemyKind::ALL`].
+    pub kills: [usize; EnemyKind::ALL.len()],
+    pub gun_kills: usize,
+    pub ball_kills: usize,
+    pub reflected_kills: usize,
+    pub shots_fired: usize,
+    /// Shots that hit at least one enemy.
+    pub shots_hit: usize,
+    pub max_ball_speed: f32,
+    pub captures: usize,
+    pub cycles: usize,
+    pub ammo_spent: usize,
+    pub ammo_gained: usize,
+    pub enemy_damage: usize,
+    pub projectile_damage: usize,
+    pub ball_damage: usize,
+    prev_ammo: Option<usize>,
+}
+
+impl RunStats {
+    pub fn kills_of(&self, kind: EnemyKind) -> usize {
+        EnemyKind::ALL
+            .iter()
+            .position(|k| *k == kind)
+            .map_or(0, |i| self.kills[i])
+    }
+
+    pub fn total_kills(&self) -> usize {
+        self.kills.iter().sum()
+    }
+
+    /// Ratio of shots that hit an enemy, `0` when nothing has been fired.
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.
+        } else {
+            self.shots_hit as f32 / self.shots_fired as f32
+        }
+    }
+
+    pub fn damage_taken(&self) -> usize {
+        self.enemy_damage + self.projectile_damage + self.ball_damage
+    }
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn record_run_stats(
+    mut stats: ResMut<RunStats>,
+    mut killed_r: EventReader<EnemyKilled>,
+    mut shot_hit_r: EventReader<ShotHit>,
+    mut captured_r: EventReader<BallCaptured>,
+    mut gesture_r: EventReader<GesturePerformed>,
+    mut damaged_r: EventReader<CoreDamaged>,
+    shot_q: Query<&Projectile, Added<Projectile>>,
+    ball_q: Query<&Speed, With<Ball>>,
+    ammo_q: Query<&PaddleAmmo>,
+    clock: Res<ModeClock>,
+) {
+    // the hit destroying the core is read after the run has ended
+    for ev in damaged_r.read() {
+        match ev.1 {
+            DamageSource::Enemy(_) => stats.enemy_damage += 1,
+            DamageSource::EnemyProjectile => stats.projectile_damage += 1,
+            DamageSource::Ball => stats.ball_damage += 1,
+        }
+    }
+
+    let killed: Vec<_> = killed_r.read().map(|ev| (ev.kind, ev.by)).collect();
+    let shots_hit = shot_hit_r.read().count();
+    let captures = captured_r.read().count();
+    // flicks and holds aren't full turns of the paddle
+    let cycles = gesture_r
+        .read()
+        .filter(|ev| !matches!(ev.gesture, Gesture::SwitchWeapon | Gesture::SteadyAim))
+        .count();
+    if stats.ended {
+        return;
+    }
+
+    stats.survival_s = clock.elapsed_s;
+    for (kind, by) in killed {
+        if let Some(i) = EnemyKind::ALL.iter().position(|k| *k == kind) {
+            stats.kills[i] += 1;
+        }
+        match by {
+            KillSource::Gun => stats.gun_kills += 1,
+            KillSource::Ball => stats.ball_kills += 1,
+            KillSource::ReflectedProjectile => stats.reflected_kills += 1,
+        }
+    }
+    // parried projectiles are spawned targeting the core
+    stats.shots_fired += shot_q
+        .iter()
+        .filter(|projectile| projectile.target == ProjectileTarget::Enemy)
+        .count();
+    stats.shots_hit += shots_hit;
+    stats.captures += captures;
+    stats.cycles += cycles;
+    for speed in &ball_q {
+        stats.max_ball_speed = stats.max_ball_speed.max(speed.0);
+    }
+
+    let ammo: usize = ammo_q.iter().map(PaddleAmmo::ammo).sum();
+    if let Some(prev) = stats.prev_ammo {
+        stats.ammo_spent += prev.saturating_sub(ammo);
+        stats.ammo_gained += ammo.saturating_sub(prev);
+    }
+    stats.prev_ammo = Some(ammo);
+}
+
+fn end_stats_on_core_destroyed(_trigger: Trigger<CoreDestroyed>, mut stats: ResMut<RunStats>) {
+    stats.ended = true;
+}
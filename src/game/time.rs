@@ -1,9 +1,21 @@
-use bevy::prelude::*;
-use std::marker::PhantomData;
+use bevy::{math::curve::Curve, prelude::*};
+use std::{marker::PhantomData, time::Duration};
+
+use crate::{AppSet, screen::Screen};
 
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
     app.add_systems(Update, process_cooldown::<()>);
+
+    app.init_resource::<TimeDilation>()
+        .add_event::<DilateTime>()
+        .add_systems(
+            Update,
+            dilate_virtual_time
+                .in_set(AppSet::TickTimers)
+                .run_if(in_state(Screen::Game)),
+        )
+        .add_systems(OnExit(Screen::Game), reset_time_dilation);
 }
 
 #[derive(Component)]
@@ -20,6 +32,10 @@ impl<T: Send + Sync> Cooldown<T> {
             _phantom: default(),
         }
     }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.timer.remaining_secs()
+    }
 }
 
 pub fn process_cooldown<T: Send + Sync>(
@@ -35,3 +51,117 @@ pub fn process_cooldown<T: Send + Sync>(
         }
     }
 }
+
+/// Request to scale [`Time<Virtual>`] for a while (hitstop, slow motion).
+///
+/// Only the active request with the highest priority is applied.
+/// Anything driven by [`Time<Real>`] (screen transitions, paddle input) keeps running,
+/// and the scale is reset when leaving [`Screen::Game`] so menus are never dilated.
+#[derive(Event, Debug, Clone)]
+pub struct DilateTime {
+    pub scale: f32,
+    pub duration_ms: u64,
+    pub priority: u8,
+    /// Eases the scale back to `1.0` over the duration when set,
+    /// otherwise the scale is held for the whole duration.
+    pub ease: Option<EaseFunction>,
+}
+
+impl DilateTime {
+    pub fn freeze(duration_ms: u64) -> Self {
+        Self {
+            scale: 0.,
+            duration_ms,
+            priority: 0,
+            ease: None,
+        }
+    }
+
+    pub fn slow_motion(scale: f32, duration_ms: u64) -> Self {
+        Self {
+            scale,
+            duration_ms,
+            priority: 0,
+            ease: Some(EaseFunction::QuadraticIn),
+        }
+    }
+
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_ease(mut self, ease: EaseFunction) -> Self {
+        self.ease = Some(ease);
+        self
+    }
+
+    fn scale_at(&self, factor: f32) -> f32 {
+        match self.ease {
+            Some(ease) => self.scale.lerp(1., ease.sample_clamped(factor)),
+            None => self.scale,
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct TimeDilation {
+    active: Vec<(DilateTime, Timer)>,
+    /// Scale all requests are multiplied by, `1.0` outside of the sandbox slow motion.
+    pub base_scale: f32,
+}
+
+impl Default for TimeDilation {
+    fn default() -> Self {
+        Self {
+            active: default(),
+            base_scale: 1.,
+        }
+    }
+}
+
+impl TimeDilation {
+    pub fn is_active(&self) -> bool {
+        !self.active.is_empty()
+    }
+}
+
+fn dilate_virtual_time(
+    mut ev_r: EventReader<DilateTime>,
+    mut dilation: ResMut<TimeDilation>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    for ev in ev_r.read() {
+        dilation.active.push((
+            ev.clone(),
+            Timer::new(Duration::from_millis(ev.duration_ms), TimerMode::Once),
+        ));
+    }
+
+    for (_, timer) in &mut dilation.active {
+        timer.tick(real_time.delta());
+    }
+    dilation.active.retain(|(_, timer)| !timer.finished());
+
+    // later requests win ties
+    let scale = dilation
+        .active
+        .iter()
+        .max_by_key(|(req, _)| req.priority)
+        .map_or(1., |(req, timer)| req.scale_at(timer.fraction()))
+        * dilation.base_scale;
+
+    if (virtual_time.relative_speed() - scale).abs() > f32::EPSILON {
+        virtual_time.set_relative_speed(scale.max(0.));
+    }
+}
+
+fn reset_time_dilation(
+    mut dilation: ResMut<TimeDilation>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    dilation.active.clear();
+    dilation.base_scale = 1.;
+    virtual_time.set_relative_speed(1.);
+}
@@ -0,0 +1,256 @@
+//! Interactive tutorial played in the arena, one mechanic at a time.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    screen::{NextTransitionedState, Screen, in_game_state},
+    theme::palette::{COL_BALL, LABEL_TEXT},
+};
+
+use super::{
+    ball::BallReflected,
+    gesture::{Gesture, GesturePerformed, GestureRecognizer},
+    input::{ActiveInput, PlayerAction},
+    movement::{AccumulatedRotation, Speed},
+    spawn::{
+        enemy::{Enemy, EnemyKind, SpawnEnemy},
+        level::Core,
+        paddle::{PaddleMode, PaddleRotation},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<TutorialRun>()
+        .init_resource::<TutorialProgress>()
+        .add_systems(OnEnter(Screen::Game), spawn_prompt.run_if(tutorial_run))
+        .add_systems(
+            Update,
+            (freeze_dummy, advance_tutorial, update_prompt)
+                .chain()
+                .run_if(in_game_state.and(tutorial_run)),
+        );
+}
+
+/// Angle the paddle has to be turned by to finish the aiming step.
+const AIM_ANGLE: f32 = PI;
+const DUMMY_DISTANCE: f32 = 650.;
+/// Time the completion message is shown before returning to the title screen.
+const DONE_DURATION_S: f32 = 3.;
+
+/// The current run is the tutorial.
+#[derive(Resource, Debug, Default)]
+pub struct TutorialRun(pub bool);
+
+pub fn tutorial_run(tutorial: Res<TutorialRun>) -> bool {
+    tutorial.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum TutorialStep {
+    #[default]
+    Aim,
+    Shoot,
+    Reflect,
+    CaptureRelease,
+    AmmoCycle,
+    RecallCycle,
+    Done,
+}
+
+impl TutorialStep {
+    const COUNT: usize = 6;
+
+    fn next(&self) -> Self {
+        match self {
+            TutorialStep::Aim => TutorialStep::Shoot,
+            TutorialStep::Shoot => TutorialStep::Reflect,
+            TutorialStep::Reflect => TutorialStep::CaptureRelease,
+            TutorialStep::CaptureRelease => TutorialStep::AmmoCycle,
+            TutorialStep::AmmoCycle => TutorialStep::RecallCycle,
+            TutorialStep::RecallCycle | TutorialStep::Done => TutorialStep::Done,
+        }
+    }
+
+    fn prompt(&self, input: ActiveInput) -> String {
+        let aim = PlayerAction::AimGamepad.binding_label(input);
+        let shoot = PlayerAction::Shoot.binding_label(input);
+        let toggle = PlayerAction::TogglePaddleMode.binding_label(input);
+        match self {
+            TutorialStep::Aim => format!("{aim}: AIM THE PADDLE AROUND THE CORE"),
+            TutorialStep::Shoot => format!("{shoot}: SHOOT THE DUMMY"),
+            TutorialStep::Reflect => {
+                format!("{toggle}: RELEASE THE SAWBLADE\nBOUNCE IT BACK WITH THE PADDLE")
+            }
+            TutorialStep::CaptureRelease => format!(
+                "{toggle}: SWITCH TO CAPTURE MODE\nCATCH THE SAWBLADE FROM THE INSIDE\n{toggle}: RELEASE IT"
+            ),
+            TutorialStep::AmmoCycle => {
+                "SPIN THE PADDLE ONE FULL CIRCLE COUNTER-CLOCKWISE\nTO GAIN AMMO".to_string()
+            }
+            TutorialStep::RecallCycle => {
+                "SPIN THE PADDLE TWO FULL CIRCLES CLOCKWISE\nTO RECALL THE SAWBLADE".to_string()
+            }
+            TutorialStep::Done => "ALL DONE, BACK TO THE TITLE SCREEN!".to_string(),
+        }
+    }
+
+    /// Gesture the step is waiting for.
+    fn gesture(&self) -> Option<Gesture> {
+        match self {
+            TutorialStep::AmmoCycle => Some(Gesture::GainAmmo),
+            TutorialStep::RecallCycle => Some(Gesture::RecallBall),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+struct TutorialProgress {
+    step: TutorialStep,
+    step_s: f32,
+    /// Paddle rotation when the aiming step started.
+    start_rotation: Option<f32>,
+    /// The ball has been captured during the capture step.
+    captured: bool,
+}
+
+#[derive(Component, Debug)]
+struct TutorialStepText;
+
+#[derive(Component, Debug)]
+struct TutorialPromptText;
+
+fn spawn_prompt(mut cmd: Commands, mut progress: ResMut<TutorialProgress>) {
+    *progress = TutorialProgress::default();
+    cmd.spawn((
+        Name::new("tutorial_prompt"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.),
+            width: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Game),
+        children![
+            (
+                TutorialStepText,
+                Text::default(),
+                TextFont::from_font_size(20.),
+                TextColor(COL_BALL),
+            ),
+            (
+                TutorialPromptText,
+                Text::default(),
+                TextFont::from_font_size(28.),
+                TextColor(LABEL_TEXT),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ),
+        ],
+    ));
+}
+
+/// The dummy stays put so it can only be destroyed by the player.
+fn freeze_dummy(mut speed_q: Query<&mut Speed, Added<Enemy>>) {
+    for mut speed in &mut speed_q {
+        speed.0 = 0.;
+    }
+}
+
+fn advance_tutorial(
+    mut progress: ResMut<TutorialProgress>,
+    rot_q: Query<&AccumulatedRotation, With<PaddleRotation>>,
+    paddle_mode_q: Query<&PaddleMode>,
+    enemy_q: Query<(), With<Enemy>>,
+    core_q: Query<(Entity, &GlobalTransform), With<Core>>,
+    mut reflected_r: EventReader<BallReflected>,
+    mut gesture_r: EventReader<GesturePerformed>,
+    mut next: ResMut<NextTransitionedState>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let reflected = reflected_r.read().count() > 0;
+    let gestures: Vec<_> = gesture_r.read().map(|ev| ev.gesture).collect();
+
+    let prev_step_s = progress.step_s;
+    progress.step_s += time.delta_secs();
+
+    let done = match progress.step {
+        TutorialStep::Aim => {
+            let Ok(acc) = rot_q.single() else {
+                return;
+            };
+            let start = *progress.start_rotation.get_or_insert(acc.rotation);
+            (acc.rotation - start).abs() >= AIM_ANGLE
+        }
+        // give the dummy a moment to spawn
+        TutorialStep::Shoot => progress.step_s > 0.5 && enemy_q.is_empty(),
+        TutorialStep::Reflect => reflected,
+        TutorialStep::CaptureRelease => {
+            let captured = paddle_mode_q
+                .iter()
+                .any(|mode| matches!(mode, PaddleMode::Captured { .. }));
+            let released = progress.captured && !captured;
+            progress.captured |= captured;
+            released
+        }
+        TutorialStep::AmmoCycle | TutorialStep::RecallCycle => progress
+            .step
+            .gesture()
+            .is_some_and(|gesture| gestures.contains(&gesture)),
+        TutorialStep::Done => {
+            if prev_step_s < DONE_DURATION_S && progress.step_s >= DONE_DURATION_S {
+                next.set(Screen::Title);
+            }
+            false
+        }
+    };
+    if !done {
+        return;
+    }
+
+    *progress = TutorialProgress {
+        step: progress.step.next(),
+        ..default()
+    };
+    if progress.step == TutorialStep::Shoot
+        && let Ok((core_e, core_t)) = core_q.single()
+    {
+        cmd.trigger(SpawnEnemy {
+            kind: EnemyKind::Creepinek,
+            position: core_t.translation().truncate() + Vec2::Y * DUMMY_DISTANCE,
+            core_e,
+            garbage: false,
+        });
+    }
+}
+
+fn update_prompt(
+    progress: Res<TutorialProgress>,
+    input: Res<State<ActiveInput>>,
+    recognizer_q: Query<&GestureRecognizer>,
+    mut step_text_q: Query<&mut Text, (With<TutorialStepText>, Without<TutorialPromptText>)>,
+    mut prompt_text_q: Query<&mut Text, (With<TutorialPromptText>, Without<TutorialStepText>)>,
+) {
+    for mut text in &mut step_text_q {
+        text.0 = match progress.step {
+            TutorialStep::Done => "TUTORIAL COMPLETE".to_string(),
+            step => format!("STEP {}/{}", step as usize + 1, TutorialStep::COUNT),
+        };
+    }
+
+    let mut prompt = progress.step.prompt(*input.get());
+    if let Some(gesture) = progress.step.gesture()
+        && let Ok(recognizer) = recognizer_q.single()
+    {
+        prompt += &format!("\n{:.0}%", recognizer.progress(gesture) * 100.);
+    }
+    for mut text in &mut prompt_text_q {
+        text.0.clone_from(&prompt);
+    }
+}
@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
-use bevy::prelude::*;
+use bevy::{ecs::component::Mutable, prelude::*};
 use bevy_tweening::*;
 use std::{marker::PhantomData, time::Duration};
 
+use super::pool::PoolCommandsExt;
+
 #[derive(Component)]
 pub enum DespawnOnTweenCompleted {
     Itself,
@@ -37,9 +39,16 @@ impl<T: Send + Sync> TweenFactor<T> {
     pub fn factor(&self) -> f32 {
         self.timer.fraction()
     }
+
+    pub fn eased_factor(&self) -> f32 {
+        self.ease.sample_clamped(self.factor())
+    }
 }
 
-pub fn tween_factor<T: Send + Sync>(mut factor_q: Query<&mut TweenFactor<T>>, time: Res<Time>) {
+pub fn tween_factor<T: Send + Sync>(
+    mut factor_q: Query<&mut TweenFactor<T>>,
+    time: Res<Time<Real>>,
+) {
     for mut factor in &mut factor_q {
         if let Some(delay) = factor.delay.as_mut() {
             delay.tick(time.delta());
@@ -57,11 +66,46 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         (
             component_animator_system::<BackgroundColor>,
+            catch_up_real_time_animators::<Transform>.after(AnimationSystem::AnimationUpdate),
+            catch_up_real_time_animators::<BackgroundColor>
+                .after(component_animator_system::<BackgroundColor>),
             despawn_after_tween,
         ),
     );
 }
 
+/// Animators of the entity keep going at the real pace through hitstops and slow motion,
+/// for UI and other feedback that isn't a part of the action.
+#[derive(Component, Debug, Default)]
+pub struct RealTimeTween;
+
+/// The tweening plugin ticks every animator by the virtual time,
+/// the ones on [`RealTimeTween`] entities get the rest of the real time on top of it.
+fn catch_up_real_time_animators<T: Component<Mutability = Mutable>>(
+    mut animator_q: Query<(Entity, &mut T, &mut Animator<T>), With<RealTimeTween>>,
+    real_time: Res<Time<Real>>,
+    time: Res<Time>,
+    events: ResMut<Events<TweenCompleted>>,
+    mut cmd: Commands,
+) {
+    let missing = real_time.delta().saturating_sub(time.delta());
+    if missing.is_zero() {
+        return;
+    }
+
+    let mut events: Mut<Events<TweenCompleted>> = events.into();
+    for (e, target, mut animator) in &mut animator_q {
+        if animator.state == AnimatorState::Paused {
+            continue;
+        }
+        let delta = missing.mul_f32(animator.speed());
+        let mut target = ComponentTarget::new(target);
+        animator
+            .tweenable_mut()
+            .tick(delta, &mut target, e, &mut events, &mut cmd);
+    }
+}
+
 fn despawn_after_tween(
     mut cmd: Commands,
     mut ev_r: EventReader<TweenCompleted>,
@@ -73,7 +117,8 @@ fn despawn_after_tween(
                 DespawnOnTweenCompleted::Itself => ev.entity,
                 DespawnOnTweenCompleted::Entity(e) => *e,
             };
-            cmd.entity(e).despawn();
+            // pooled entities are parked instead
+            cmd.release(e);
         }
     }
 }
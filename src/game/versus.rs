@@ -0,0 +1,62 @@
+//! Local versus mode where each player guards their own core.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::GAME_SIZE;
+
+use super::{
+    player::{Multiplayer, Player},
+    spawn::{
+        enemy::{EnemyKind, SpawnEnemy},
+        level::Core,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(send_garbage);
+}
+
+const GARBAGE_SPAWN_DISTANCE: f32 = GAME_SIZE * 0.6;
+
+/// Outcome of the last versus match.
+#[derive(Resource, Debug)]
+pub struct VersusResult {
+    /// `None` when both cores went down at once.
+    pub winner: Option<Player>,
+}
+
+/// An enemy attacking the given core has been killed, send one to the opponent.
+#[derive(Event, Debug)]
+pub struct SendGarbage {
+    pub from_core_e: Entity,
+}
+
+/// Enemy sent by the opponent, killing it doesn't send any more garbage back.
+#[derive(Component, Debug)]
+pub struct Garbage;
+
+fn send_garbage(
+    trigger: Trigger<SendGarbage>,
+    core_q: Query<(Entity, &GlobalTransform), With<Core>>,
+    multiplayer: Res<Multiplayer>,
+    mut cmd: Commands,
+) {
+    if *multiplayer != Multiplayer::Versus {
+        return;
+    }
+
+    let mut rng = thread_rng();
+    for (core_e, core_t) in core_q
+        .iter()
+        .filter(|(e, _)| *e != trigger.event().from_core_e)
+    {
+        let dir = Rot2::degrees(rng.gen_range(-180.0..180.0)) * Vec2::X;
+        cmd.trigger(SpawnEnemy {
+            kind: EnemyKind::Creepinek,
+            position: core_t.translation().truncate() + dir * GARBAGE_SPAWN_DISTANCE,
+            core_e,
+            garbage: true,
+        });
+    }
+}
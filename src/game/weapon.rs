@@ -0,0 +1,287 @@
+//! Paddle weapons and their definitions.
+
+use bevy::prelude::*;
+use bevy_tweening::Animator;
+
+use crate::{
+    screen::{Screen, in_game_state},
+    theme::palette::COL_BULLET,
+};
+
+use super::{
+    gesture::{Gesture, GesturePerformed},
+    input::{PlayerAction, PlayerInput},
+    mode::RunRules,
+    progression::Progression,
+    spawn::paddle::{Paddle, PaddleRotation},
+    time::{Cooldown, process_cooldown},
+    tween::{DespawnOnTweenCompleted, RealTimeTween, delay_tween, get_relative_scale_tween},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (switch_weapon, steady_aim, process_cooldown::<SteadyAim>).run_if(in_game_state),
+    );
+}
+
+/// Time shots stay perfectly accurate after the paddle has been held still.
+const STEADY_AIM_S: f32 = 1.5;
+
+/// Shots are fired without any spread.
+pub struct SteadyAim;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum WeaponKind {
+    #[default]
+    PeaShooter,
+    SpreadShot,
+    PiercingRail,
+    BurstRifle,
+    HeavyCannon,
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 5] = [
+        WeaponKind::PeaShooter,
+        WeaponKind::SpreadShot,
+        WeaponKind::PiercingRail,
+        WeaponKind::BurstRifle,
+        WeaponKind::HeavyCannon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponKind::PeaShooter => "PEA SHOOTER",
+            WeaponKind::SpreadShot => "SPREAD SHOT",
+            WeaponKind::PiercingRail => "PIERCING RAIL",
+            WeaponKind::BurstRifle => "BURST RIFLE",
+            WeaponKind::HeavyCannon => "HEAVY CANNON",
+        }
+    }
+
+    pub fn def(&self) -> WeaponDef {
+        match self {
+            WeaponKind::PeaShooter => WeaponDef::default(),
+            WeaponKind::SpreadShot => WeaponDef {
+                ammo_cost: 3,
+                cooldown: (0.45, 0.3),
+                projectile_count: 5,
+                fan_angle: 30.,
+                accuracy_spread: 3.,
+                speed: 1400.,
+                scale: Vec2::splat(0.8),
+                impulse: 20.,
+                trauma: (0.25, 0.15),
+                knockback: -12.,
+                ..default()
+            },
+            WeaponKind::PiercingRail => WeaponDef {
+                ammo_cost: 4,
+                cooldown: (0.6, 0.4),
+                accuracy_spread: 0.,
+                speed: 3200.,
+                scale: Vec2::new(0.8, 1.8),
+                damage: 2,
+                pierce: 4,
+                impulse: 45.,
+                trauma: (0.3, 0.2),
+                knockback: -14.,
+                ..default()
+            },
+            WeaponKind::BurstRifle => WeaponDef {
+                ammo_cost: 3,
+                cooldown: (0.5, 0.3),
+                burst_count: 3,
+                burst_interval: 0.06,
+                accuracy_spread: 2.5,
+                speed: 1900.,
+                ..default()
+            },
+            WeaponKind::HeavyCannon => WeaponDef {
+                ammo_cost: 8,
+                cooldown: (1.0, 0.7),
+                accuracy_spread: 0.,
+                speed: 900.,
+                scale: Vec2::splat(2.2),
+                damage: 5,
+                pierce: 2,
+                impulse: 90.,
+                trauma: (0.4, 0.3),
+                knockback: -20.,
+                ..default()
+            },
+        }
+    }
+}
+
+/// Tuning values of a single weapon.
+#[derive(Debug, Clone)]
+pub struct WeaponDef {
+    pub ammo_cost: usize,
+    /// Cooldown between shots at the lowest and highest ball speed.
+    pub cooldown: (f32, f32),
+    pub projectile_count: u8,
+    /// Total angle in degrees the projectiles of a single shot are spread over.
+    pub fan_angle: f32,
+    pub accuracy_spread: f32,
+    pub speed: f32,
+    pub scale: Vec2,
+    pub damage: u8,
+    pub pierce: u8,
+    /// Knockback applied to hit enemies.
+    pub impulse: f32,
+    /// Number of shots fired per trigger pull, only the first one costs ammo.
+    pub burst_count: u8,
+    pub burst_interval: f32,
+    /// Screen shake at the lowest and highest ball speed.
+    pub trauma: (f32, f32),
+    pub knockback: f32,
+}
+
+impl Default for WeaponDef {
+    fn default() -> Self {
+        Self {
+            ammo_cost: 1,
+            cooldown: (0.17, 0.09),
+            projectile_count: 1,
+            fan_angle: 0.,
+            accuracy_spread: 4.5,
+            speed: 1600.,
+            scale: Vec2::ONE,
+            damage: 1,
+            pierce: 0,
+            impulse: 30.,
+            burst_count: 1,
+            burst_interval: 0.,
+            trauma: (0.165, 0.085),
+            knockback: -8.,
+        }
+    }
+}
+
+impl WeaponDef {
+    /// Shot fired by holding the charge button, whatever the equipped weapon is.
+    pub fn charged_shot() -> Self {
+        Self {
+            ammo_cost: 15,
+            cooldown: (0.4, 0.4),
+            accuracy_spread: 0.,
+            speed: 1100.,
+            scale: Vec2::splat(2.5),
+            damage: 6,
+            pierce: 5,
+            impulse: 180.,
+            trauma: (0.5, 0.5),
+            knockback: -25.,
+            ..default()
+        }
+    }
+
+    pub fn cooldown(&self, ball_speed_factor: f32) -> f32 {
+        self.cooldown.0.lerp(self.cooldown.1, ball_speed_factor)
+    }
+
+    pub fn trauma(&self, ball_speed_factor: f32) -> f32 {
+        self.trauma.0.lerp(self.trauma.1, ball_speed_factor)
+    }
+
+    /// Directions of all projectiles of a single shot.
+    pub fn directions(&self, dir: Dir2) -> impl Iterator<Item = Dir2> {
+        let count = self.projectile_count.max(1);
+        let step = if count > 1 {
+            self.fan_angle / (count - 1) as f32
+        } else {
+            0.
+        };
+        let start = -self.fan_angle / 2.;
+        (0..count).map(move |i| Rot2::degrees(start + step * i as f32) * dir)
+    }
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    /// Shots left in the current burst.
+    pub burst_remaining: u8,
+    /// Fire a single shot even if the trigger isn't held.
+    pub queued: bool,
+}
+
+/// Weapons that can be switched to in the current run.
+/// Without the loadout only the pea shooter is available, so shared runs stay comparable.
+fn available_weapons(rules: &RunRules, progression: &Progression) -> Vec<WeaponKind> {
+    WeaponKind::ALL
+        .into_iter()
+        .filter(|&kind| {
+            kind == WeaponKind::PeaShooter || (rules.loadout && progression.weapon_unlocked(kind))
+        })
+        .collect()
+}
+
+/// Paddles that performed the gesture this frame.
+fn gesture_paddles(
+    gesture_r: &mut EventReader<GesturePerformed>,
+    rot_q: &Query<&PaddleRotation>,
+    gesture: Gesture,
+) -> Vec<Entity> {
+    gesture_r
+        .read()
+        .filter(|ev| ev.gesture == gesture)
+        .filter_map(|ev| rot_q.get(ev.entity).ok())
+        .map(|rot| rot.paddle_e)
+        .collect()
+}
+
+/// Switches with the button or by flicking the paddle.
+fn switch_weapon(
+    mut weapon_q: Query<(Entity, &mut Weapon, &PlayerInput, &GlobalTransform), With<Paddle>>,
+    mut gesture_r: EventReader<GesturePerformed>,
+    rot_q: Query<&PaddleRotation>,
+    rules: Res<RunRules>,
+    progression: Res<Progression>,
+    mut cmd: Commands,
+) {
+    let flicked = gesture_paddles(&mut gesture_r, &rot_q, Gesture::SwitchWeapon);
+    let weapons = available_weapons(&rules, &progression);
+    for (e, mut weapon, input, t) in &mut weapon_q {
+        let switch = input.just_pressed(&PlayerAction::SwitchWeapon) || flicked.contains(&e);
+        if !switch || weapons.len() < 2 {
+            continue;
+        }
+
+        let i = weapons
+            .iter()
+            .position(|k| *k == weapon.kind)
+            .unwrap_or_default();
+        weapon.kind = weapons[(i + 1) % weapons.len()];
+        weapon.burst_remaining = 0;
+        weapon.queued = false;
+
+        cmd.spawn((
+            Name::new("weapon_label"),
+            Text2d::new(weapon.kind.name()),
+            TextFont::from_font_size(32.),
+            TextColor(COL_BULLET),
+            Transform::from_translation((t.translation().truncate() * 0.75).extend(5.)),
+            Animator::new(delay_tween(
+                get_relative_scale_tween(Vec2::ZERO.extend(1.), 250, Some(EaseFunction::BackIn)),
+                600,
+            )),
+            DespawnOnTweenCompleted::Itself,
+            RealTimeTween,
+            StateScoped(Screen::Game),
+        ));
+    }
+}
+
+fn steady_aim(
+    mut gesture_r: EventReader<GesturePerformed>,
+    rot_q: Query<&PaddleRotation>,
+    mut cmd: Commands,
+) {
+    for paddle_e in gesture_paddles(&mut gesture_r, &rot_q, Gesture::SteadyAim) {
+        cmd.entity(paddle_e)
+            .try_insert(Cooldown::<SteadyAim>::new(STEADY_AIM_S));
+    }
+}
@@ -4,10 +4,16 @@ mod event;
 mod ext;
 mod game;
 mod math;
+mod save;
 mod screen;
+pub mod sim;
+mod storage;
 mod theme;
 
+use std::time::Duration;
+
 use bevy::{
+    app::ScheduleRunnerPlugin,
     asset::AssetMetaCheck,
     audio::{AudioPlugin, Volume},
     core_pipeline::{
@@ -15,7 +21,10 @@ use bevy::{
         tonemapping::Tonemapping,
     },
     prelude::*,
-    render::camera::ScalingMode,
+    render::{RenderPlugin, camera::ScalingMode, settings::WgpuSettings},
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+    winit::WinitPlugin,
 };
 use bevy_trauma_shake::ShakeSettings;
 
@@ -73,15 +82,7 @@ impl Plugin for AppPlugin {
                 }),
         );
 
-        // Add project plugins.
-        app.add_plugins((game::plugin, screen::plugin, theme::plugin));
-
-        // Add external plugins
-        app.add_plugins((
-            avian2d::PhysicsPlugins::default(),
-            bevy_trauma_shake::TraumaPlugin,
-            bevy_enoki::EnokiPlugin,
-        ));
+        add_game_plugins(app);
 
         // Enable dev tools for dev builds.
         #[cfg(feature = "dev")]
@@ -89,6 +90,64 @@ impl Plugin for AppPlugin {
     }
 }
 
+/// The game without a window, rendering or waiting for real time, used by the balance simulator.
+pub struct HeadlessAppPlugin;
+
+/// Game time that passes every update of the headless app.
+pub const HEADLESS_TIME_STEP: Duration = Duration::from_micros(16_667);
+
+impl Plugin for HeadlessAppPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (AppSet::TickTimers, AppSet::ProcessInput, AppSet::Update).chain(),
+        );
+        app.add_systems(Startup, spawn_camera);
+
+        app.add_plugins(
+            DefaultPlugins
+                .build()
+                .set(AssetPlugin {
+                    meta_check: AssetMetaCheck::Never,
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .set(ImagePlugin::default_linear())
+                .disable::<WinitPlugin>()
+                // update as fast as possible
+                .add(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        );
+        // fixed step, so the game runs faster than real time
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_TIME_STEP));
+
+        add_game_plugins(app);
+    }
+}
+
+fn add_game_plugins(app: &mut App) {
+    // Add project plugins.
+    app.add_plugins((game::plugin, save::plugin, screen::plugin, theme::plugin));
+
+    // Add external plugins
+    app.add_plugins((
+        avian2d::PhysicsPlugins::default(),
+        bevy_trauma_shake::TraumaPlugin,
+        bevy_enoki::EnokiPlugin,
+    ));
+}
+
 /// High-level groupings of systems for the app in the `Update` schedule.
 /// When adding a new variant, make sure to order it in the `configure_sets`
 /// call above.
@@ -0,0 +1,265 @@
+//! Save profiles, each one a versioned RON file written through [`storage`].
+//!
+//! Bump [`SAVE_VERSION`] on breaking changes to [`ProfileData`] and add a step from the
+//! old version to [`migrate`], new fields with a default don't need one.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    game::{achievement::Achievements, daily::DailyBest, progression::Progression},
+    storage,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let mut profiles = Profiles::load();
+    let data = profiles.load_active();
+    app.insert_resource(profiles)
+        .insert_resource(data.daily_best)
+        .insert_resource(data.achievements)
+        .insert_resource(data.progression)
+        .add_event::<SwitchProfile>()
+        .add_systems(
+            Last,
+            (save_profile.run_if(profile_changed), switch_profile).chain(),
+        );
+}
+
+pub const PROFILE_COUNT: usize = 3;
+const SAVE_VERSION: u32 = 2;
+const PROFILES_KEY: &str = "profiles.ron";
+
+/// Everything saved per profile.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileData {
+    pub daily_best: DailyBest,
+    pub achievements: Achievements,
+    pub progression: Progression,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveFile<T> {
+    version: u32,
+    data: T,
+}
+
+/// Reads just the version, so the rest can be parsed by the matching format.
+#[derive(Debug, Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfileIndex {
+    active: usize,
+}
+
+/// Profile in use, data of the others stays on disk until they are switched to.
+#[derive(Resource, Debug, Default)]
+pub struct Profiles {
+    active: usize,
+    /// Problem with loading the save data, shown to the player.
+    pub warning: Option<String>,
+}
+
+/// Saves the active profile and loads the given one.
+#[derive(Event, Debug)]
+pub struct SwitchProfile(pub usize);
+
+impl Profiles {
+    fn load() -> Self {
+        let mut profiles = Self::default();
+        if let Some(index) = profiles.read::<ProfileIndex>(PROFILES_KEY) {
+            profiles.active = index.active.min(PROFILE_COUNT - 1);
+        }
+        profiles
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    fn profile_key(slot: usize) -> String {
+        format!("profile_{slot}.ron")
+    }
+
+    fn load_active(&mut self) -> ProfileData {
+        self.read(&Self::profile_key(self.active))
+            .unwrap_or_default()
+    }
+
+    /// Falls back to the backup and then to `None` when the save can't be read.
+    /// Unreadable saves are quarantined first, as the key is saved over with whatever is used.
+    fn read<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        let saved = storage::load(key)?;
+        match migrate(&saved) {
+            Ok(data) => return Some(data),
+            Err(err) => {
+                warn!(key, %err, "failed to read the save, trying the backup");
+                storage::quarantine(key, &quarantine_suffix(&saved), &saved);
+            }
+        }
+
+        match storage::load_backup(key).map(|saved| (migrate(&saved), saved)) {
+            Some((Ok(data), _)) => {
+                self.warning = Some("SAVE DATA WAS DAMAGED, RESTORED A BACKUP".to_string());
+                Some(data)
+            }
+            Some((Err(err), saved)) => {
+                warn!(key, %err, "failed to read the backup");
+                storage::quarantine(key, &format!("bak.{}", quarantine_suffix(&saved)), &saved);
+                self.warning = Some("SAVE DATA WAS DAMAGED, STARTED OVER".to_string());
+                None
+            }
+            None => {
+                self.warning = Some("SAVE DATA WAS DAMAGED, STARTED OVER".to_string());
+                None
+            }
+        }
+    }
+}
+
+/// Saves of a newer version are kept by their version, so they can be moved back after an update.
+fn quarantine_suffix(saved: &str) -> String {
+    match ron::from_str::<SaveVersion>(saved) {
+        Ok(SaveVersion { version }) if version > SAVE_VERSION => format!("v{version}"),
+        _ => "corrupt".to_string(),
+    }
+}
+
+/// Parses a save of any known version into the current format,
+/// upgrading it one version at a time.
+fn migrate<T: DeserializeOwned>(saved: &str) -> Result<T, String> {
+    let SaveVersion { version } = ron::from_str(saved).map_err(|err| err.to_string())?;
+    if version > SAVE_VERSION {
+        return Err(format!("saved by a newer version ({version})"));
+    }
+
+    let mut saved = saved.to_string();
+    for version in version..SAVE_VERSION {
+        saved = match version {
+            1 => migrate_v1(&saved),
+            _ => return Err(format!("unknown version {version}")),
+        };
+    }
+    ron::from_str::<SaveFile<T>>(&saved)
+        .map(|file| file.data)
+        .map_err(|err| err.to_string())
+}
+
+/// The charged shot unlock became the heavy cannon,
+/// the name went to the shot charged by holding fire with any weapon.
+fn migrate_v1(saved: &str) -> String {
+    saved.replace("charged_shot", "heavy_cannon")
+}
+
+fn write<T: Serialize>(key: &str, data: &T) {
+    let file = SaveFile {
+        version: SAVE_VERSION,
+        data,
+    };
+    match ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default()) {
+        Ok(saved) => storage::save(key, &saved),
+        Err(err) => warn!(key, ?err, "failed to serialize the save"),
+    }
+}
+
+/// Skips the first frame, the freshly loaded profile has nothing new to save.
+fn profile_changed(
+    daily_best: Res<DailyBest>,
+    achievements: Res<Achievements>,
+    progression: Res<Progression>,
+) -> bool {
+    (daily_best.is_changed() && !daily_best.is_added())
+        || (achievements.is_changed() && !achievements.is_added())
+        || (progression.is_changed() && !progression.is_added())
+}
+
+fn save_profile(
+    profiles: Res<Profiles>,
+    daily_best: Res<DailyBest>,
+    achievements: Res<Achievements>,
+    progression: Res<Progression>,
+) {
+    let data = ProfileData {
+        daily_best: daily_best.clone(),
+        achievements: achievements.clone(),
+        progression: progression.clone(),
+    };
+    write(&Profiles::profile_key(profiles.active), &data);
+}
+
+fn switch_profile(
+    mut switch_r: EventReader<SwitchProfile>,
+    mut profiles: ResMut<Profiles>,
+    mut cmd: Commands,
+) {
+    let Some(ev) = switch_r.read().last() else {
+        return;
+    };
+    profiles.active = ev.0.min(PROFILE_COUNT - 1);
+    profiles.warning = None;
+    write(
+        PROFILES_KEY,
+        &ProfileIndex {
+            active: profiles.active,
+        },
+    );
+
+    let data = profiles.load_active();
+    cmd.insert_resource(data.daily_best);
+    cmd.insert_resource(data.achievements);
+    cmd.insert_resource(data.progression);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::progression::Unlock;
+
+    #[test]
+    fn migrates_renamed_unlocks_from_v1() {
+        let saved = "(version: 1, data: (progression: (scrap: 30, owned: [piercing_rail, charged_shot], equipped: [charged_shot])))";
+        let data = migrate::<ProfileData>(saved).unwrap();
+        assert_eq!(data.progression.scrap, 30);
+        assert!(data.progression.owns(Unlock::PiercingRail));
+        assert!(data.progression.owns(Unlock::HeavyCannon));
+        assert!(data.progression.is_equipped(Unlock::HeavyCannon));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let version = SAVE_VERSION + 1;
+        let saved = format!("(version: {version}, data: ())");
+        assert!(migrate::<ProfileData>(&saved).is_err());
+        assert_eq!(quarantine_suffix(&saved), format!("v{version}"));
+    }
+
+    #[test]
+    fn corrupted_save_falls_back_to_backup() {
+        let key = "corrupted_with_backup.ron";
+        let data = ProfileData {
+            daily_best: DailyBest { day: 7, score: 120 },
+            ..default()
+        };
+        write(key, &data);
+        storage::save(key, "(version: 2, data: (daily_best: (day:");
+
+        let mut profiles = Profiles::default();
+        let restored = profiles.read::<ProfileData>(key).unwrap();
+        assert_eq!(restored.daily_best.score, 120);
+        assert!(profiles.warning.is_some());
+    }
+
+    #[test]
+    fn corrupted_save_without_backup_starts_over() {
+        let key = "corrupted_without_backup.ron";
+        storage::save(key, "not a save");
+
+        let mut profiles = Profiles::default();
+        assert!(profiles.read::<ProfileData>(key).is_none());
+        assert!(profiles.warning.is_some());
+    }
+}
@@ -0,0 +1,67 @@
+//! A screen listing the achievements, accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::achievement::{Achievement, Achievements},
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Achievements), enter_achievements);
+}
+
+fn enter_achievements(mut commands: Commands, achievements: Res<Achievements>) {
+    commands
+        .spawn((
+            StateScoped(Screen::Achievements),
+            widget::ui_root("achievements"),
+            children![
+                widget::header("ACHIEVEMENTS"),
+                widget::label(format!(
+                    "{}/{} UNLOCKED",
+                    achievements.unlocked_count(),
+                    Achievement::ALL.len()
+                )),
+            ],
+        ))
+        .with_children(|b| {
+            b.spawn((
+                Name::new("Achievement List"),
+                Node {
+                    display: Display::Grid,
+                    grid_template_columns: vec![GridTrack::auto(), GridTrack::auto()],
+                    column_gap: Val::Px(24.),
+                    row_gap: Val::Px(6.),
+                    ..default()
+                },
+            ))
+            .with_children(|b| {
+                for achievement in Achievement::ALL {
+                    let def = achievement.def();
+                    let color = if achievements.is_unlocked(achievement) {
+                        ui_palette::COL_BULLET
+                    } else {
+                        ui_palette::LABEL_TEXT.with_alpha(0.4)
+                    };
+                    b.spawn((
+                        Name::new("Achievement Name"),
+                        Text::new(def.name),
+                        TextFont::from_font_size(20.),
+                        TextColor(color),
+                    ));
+                    b.spawn((
+                        Name::new("Achievement Description"),
+                        Text::new(def.description),
+                        TextFont::from_font_size(16.),
+                        TextColor(color),
+                    ));
+                }
+            });
+            b.spawn(widget::button(
+                "BACK",
+                super::enter_screen_on_pointer_click(Screen::Title),
+            ));
+        });
+}
@@ -1,31 +1,225 @@
-//! The title screen that appears when the game starts.
+//! The game over screen with a summary of the run.
 
 use bevy::prelude::*;
 
 use super::Screen;
-use crate::{game::score::Score, theme::prelude::*};
+use crate::{
+    game::{
+        daily::{DailyBest, DailyRun},
+        mode::{GameMode, ModeClock},
+        progression::Progression,
+        score::Score,
+        spawn::enemy::EnemyKind,
+        stats::RunStats,
+        tween::{TweenFactor, tween_factor},
+    },
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::GameOver), enter_game_over);
+    app.add_systems(OnEnter(Screen::GameOver), enter_game_over)
+        .add_systems(
+            Update,
+            (tween_factor::<CountUp>, count_up)
+                .chain()
+                .run_if(in_state(Screen::GameOver)),
+        );
 }
 
+/// Duration of a single stat count-up.
+const COUNT_UP_MS: u64 = 900;
+/// Delay between the start of two consecutive count-ups.
+const COUNT_UP_STAGGER_MS: u64 = 60;
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum BtnAction {
     Play,
 }
 
-fn enter_game_over(mut commands: Commands, score: Res<Score>) {
-    commands.spawn((
-        StateScoped(Screen::GameOver),
-        widget::ui_root("game_over"),
-        children![
-            widget::label("GAME OVER"),
-            widget::label(format!("SCORE: {}", score.0)),
-            widget::button(
-                "TRY AGAIN",
-                super::enter_screen_on_pointer_click(Screen::Game)
-            )
-        ],
+/// Text counting up to the stat value.
+#[derive(Component, Debug, Clone, Copy)]
+struct CountUp {
+    target: f32,
+    format: StatFormat,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StatFormat {
+    Count,
+    Percent,
+    Time,
+    Speed,
+}
+
+impl StatFormat {
+    fn format(&self, value: f32) -> String {
+        match self {
+            StatFormat::Count => format!("{value:.0}"),
+            StatFormat::Percent => format!("{:.0}%", value * 100.),
+            StatFormat::Time => {
+                let s = value as u32;
+                format!("{}:{:02}", s / 60, s % 60)
+            }
+            StatFormat::Speed => format!("{value:.0} PX/S"),
+        }
+    }
+}
+
+fn stat_rows(stats: &RunStats) -> Vec<(&'static str, f32, StatFormat)> {
+    let mut rows = vec![
+        ("TIME SURVIVED", stats.survival_s, StatFormat::Time),
+        ("KILLS", stats.total_kills() as f32, StatFormat::Count),
+    ];
+    rows.extend(
+        EnemyKind::ALL
+            .iter()
+            .map(|kind| (kind.name(), stats.kills_of(*kind) as f32, StatFormat::Count)),
+    );
+    rows.extend([
+        ("GUN KILLS", stats.gun_kills as f32, StatFormat::Count),
+        ("SAWBLADE KILLS", stats.ball_kills as f32, StatFormat::Count),
+        (
+            "PARRY KILLS",
+            stats.reflected_kills as f32,
+            StatFormat::Count,
+        ),
+        ("SHOTS FIRED", stats.shots_fired as f32, StatFormat::Count),
+        ("ACCURACY", stats.accuracy(), StatFormat::Percent),
+        (
+            "TOP SAWBLADE SPEED",
+            stats.max_ball_speed,
+            StatFormat::Speed,
+        ),
+        ("CAPTURES", stats.captures as f32, StatFormat::Count),
+        ("CYCLES", stats.cycles as f32, StatFormat::Count),
+        (
+            "HITS BY ENEMIES",
+            stats.enemy_damage as f32,
+            StatFormat::Count,
+        ),
+        (
+            "HITS BY PROJECTILES",
+            stats.projectile_damage as f32,
+            StatFormat::Count,
+        ),
+    ]);
+    // only the opponent's sawblade can hit the core
+    if stats.ball_damage > 0 {
+        rows.push((
+            "HITS BY SAWBLADES",
+            stats.ball_damage as f32,
+            StatFormat::Count,
+        ));
+    }
+    rows
+}
+
+fn enter_game_over(
+    mut commands: Commands,
+    score: Res<Score>,
+    daily: Res<DailyRun>,
+    daily_best: Res<DailyBest>,
+    mode: Res<GameMode>,
+    clock: Res<ModeClock>,
+    stats: Res<RunStats>,
+    progression: Res<Progression>,
+) {
+    let root_e = commands
+        .spawn((
+            StateScoped(Screen::GameOver),
+            widget::ui_root("game_over"),
+            children![
+                widget::label(if clock.time_up {
+                    "TIME UP"
+                } else {
+                    "GAME OVER"
+                }),
+                widget::label(format!("SCORE: {}", score.0)),
+            ],
+        ))
+        .id();
+
+    // daily challenges play by the endless rules
+    if daily.0.is_none() && *mode != GameMode::Endless {
+        commands
+            .entity(root_e)
+            .with_child(widget::label(format!("MODE: {}", mode.name())));
+    }
+
+    if let Some(challenge) = daily.0 {
+        commands.entity(root_e).with_children(|b| {
+            b.spawn(widget::label(format!(
+                "DAILY {} #{:016X}",
+                challenge.date_label(),
+                challenge.seed
+            )));
+            b.spawn(widget::label(challenge.modifier.label()));
+            if let Some(best) = daily_best.score_for(&challenge) {
+                b.spawn(widget::label(format!("BEST TODAY: {best}")));
+            }
+        });
+    }
+
+    // two stats per row
+    commands.entity(root_e).with_children(|b| {
+        b.spawn((
+            Name::new("Run Stats"),
+            Node {
+                display: Display::Grid,
+                grid_template_columns: vec![
+                    GridTrack::auto(),
+                    GridTrack::px(140.),
+                    GridTrack::auto(),
+                    GridTrack::px(140.),
+                ],
+                column_gap: Val::Px(16.),
+                row_gap: Val::Px(4.),
+                margin: UiRect::vertical(Val::Px(8.)),
+                ..default()
+            },
+        ))
+        .with_children(|b| {
+            for (i, (name, target, format)) in stat_rows(&stats).into_iter().enumerate() {
+                b.spawn((
+                    Name::new("Stat Name"),
+                    Text(name),
+                    TextFont::from_font_size(18.),
+                    TextColor(ui_palette::LABEL_TEXT),
+                ));
+                b.spawn((
+                    Name::new("Stat Value"),
+                    Text(format.format(0.)),
+                    TextFont::from_font_size(18.),
+                    TextColor(ui_palette::COL_BULLET),
+                    TextLayout::new_with_justify(JustifyText::Right),
+                    CountUp { target, format },
+                    TweenFactor::<CountUp>::new(COUNT_UP_MS, EaseFunction::QuadraticOut)
+                        .with_delay(i as u64 * COUNT_UP_STAGGER_MS),
+                ));
+            }
+        });
+    });
+
+    if progression.last_run_scrap > 0 {
+        commands.entity(root_e).with_child(widget::label(format!(
+            "+{} SCRAP ({} TOTAL)",
+            progression.last_run_scrap, progression.scrap
+        )));
+    }
+
+    commands.entity(root_e).with_child(widget::button(
+        "TRY AGAIN",
+        super::enter_screen_on_pointer_click(Screen::Game),
     ));
 }
+
+fn count_up(
+    mut text_q: Query<(&mut Text, &CountUp, &TweenFactor<CountUp>), Changed<TweenFactor<CountUp>>>,
+) {
+    for (mut text, count_up, factor) in &mut text_q {
+        text.0 = count_up
+            .format
+            .format(count_up.target * factor.eased_factor());
+    }
+}
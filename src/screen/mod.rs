@@ -1,12 +1,14 @@
 //! The game's main screen states and transitions between them.
 
+mod achievements;
 mod credits;
 mod game_over;
 mod loading;
 mod playing;
 mod splash;
 mod title;
-mod tutorial;
+mod unlocks;
+mod versus_results;
 
 use bevy::{prelude::*, window::WindowResized};
 
@@ -27,9 +29,11 @@ pub(super) fn plugin(app: &mut App) {
             loading::plugin,
             title::plugin,
             credits::plugin,
+            achievements::plugin,
+            unlocks::plugin,
             playing::plugin,
             game_over::plugin,
-            tutorial::plugin,
+            versus_results::plugin,
         ))
         .add_systems(OnExit(Screen::Loading), setup_transition_overlay)
         .add_systems(Startup, setup_letterbox)
@@ -59,10 +63,12 @@ pub enum Screen {
     Loaded,
     Title,
     Credits,
-    Tutorial,
+    Achievements,
+    Unlocks,
     Game,
     RestartGame,
     GameOver,
+    VersusResults,
     Exit,
 }
 
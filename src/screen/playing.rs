@@ -40,8 +40,9 @@ fn enter_playing(
     // reset score
     score.0 = 0;
 
-    if cfg!(not(any(target_family = "wasm", target_os = "macos"))) {
-        let mut win = window_q.single_mut().expect("window exists");
+    if cfg!(not(any(target_family = "wasm", target_os = "macos")))
+        && let Ok(mut win) = window_q.single_mut()
+    {
         win.cursor_options.grab_mode = CursorGrabMode::Locked;
     }
 }
@@ -50,8 +51,9 @@ fn exit_playing(mut commands: Commands, mut window_q: Query<&mut Window, With<Pr
     // We could use [`StateScoped`] on the sound playing entites instead.
     commands.trigger(PlayMusic::Disable);
 
-    if cfg!(not(any(target_family = "wasm", target_os = "macos"))) {
-        let mut win: Mut<'_, Window> = window_q.single_mut().expect("window exists");
+    if cfg!(not(any(target_family = "wasm", target_os = "macos")))
+        && let Ok(mut win) = window_q.single_mut()
+    {
         win.cursor_options.grab_mode = CursorGrabMode::None;
     }
 }
@@ -2,24 +2,61 @@
 
 use bevy::prelude::*;
 
-use super::Screen;
-use crate::theme::prelude::*;
+use super::{NextTransitionedState, Screen};
+use crate::{
+    game::{
+        daily::{DailyChallenge, DailyRun},
+        mode::GameMode,
+        player::Multiplayer,
+        progression::Progression,
+        rng::RunSeed,
+        tutorial::TutorialRun,
+    },
+    save::{PROFILE_COUNT, Profiles, SwitchProfile},
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), enter_title)
+        .add_systems(
+            Update,
+            respawn_title.run_if(in_state(Screen::Title).and(resource_changed::<Profiles>)),
+        )
         .add_systems(OnEnter(Screen::Exit), exit_app);
 }
 
-fn enter_title(mut commands: Commands) {
-    commands.spawn((
+#[derive(Component, Debug)]
+struct TitleRoot;
+
+fn profile_name(profiles: &Profiles) -> String {
+    format!("PROFILE {}", profiles.active() + 1)
+}
+
+fn enter_title(mut commands: Commands, mode: Res<GameMode>, profiles: Res<Profiles>) {
+    let mut root = commands.spawn((
+        TitleRoot,
         StateScoped(Screen::Title),
         widget::ui_root("menu"),
         children![
             widget::header("PORCLE"),
-            widget::button("PLAY", super::enter_screen_on_pointer_click(Screen::Game)),
+            widget::button(profile_name(&profiles), switch_profile_on_pointer_click),
+            widget::button("PLAY", play_on_pointer_click(Multiplayer::Off)),
+            widget::button(mode.name(), cycle_mode_on_pointer_click),
+            widget::button("DAILY", play_daily_on_pointer_click),
+            widget::button("CO-OP", play_on_pointer_click(Multiplayer::Coop)),
+            widget::button(
+                "CO-OP INNER",
+                play_on_pointer_click(Multiplayer::CoopInnerRail)
+            ),
+            widget::button("VERSUS", play_on_pointer_click(Multiplayer::Versus)),
+            widget::button("TUTORIAL", play_tutorial_on_pointer_click),
             widget::button(
-                "TUTORIAL",
-                super::enter_screen_on_pointer_click(Screen::Tutorial)
+                "UNLOCKS",
+                super::enter_screen_on_pointer_click(Screen::Unlocks)
+            ),
+            widget::button(
+                "ACHIEVEMENTS",
+                super::enter_screen_on_pointer_click(Screen::Achievements)
             ),
             widget::button(
                 "CREDITS",
@@ -29,6 +66,102 @@ fn enter_title(mut commands: Commands) {
             widget::button("EXIT", super::enter_screen_on_pointer_click(Screen::Exit)),
         ],
     ));
+    if let Some(warning) = &profiles.warning {
+        root.with_child(widget::label(warning.clone()));
+    }
+}
+
+/// Rebuilt once a switched profile is loaded, so the labels match it.
+fn respawn_title(
+    mut commands: Commands,
+    root_q: Query<Entity, With<TitleRoot>>,
+    mode: Res<GameMode>,
+    profiles: Res<Profiles>,
+) {
+    for e in &root_q {
+        commands.entity(e).despawn();
+    }
+    enter_title(commands, mode, profiles);
+}
+
+fn switch_profile_on_pointer_click(
+    _trigger: Trigger<Pointer<Click>>,
+    profiles: Res<Profiles>,
+    mut mode: ResMut<GameMode>,
+    mut switch_w: EventWriter<SwitchProfile>,
+) {
+    // the picked mode might be locked in the other profile
+    *mode = GameMode::default();
+    switch_w.write(SwitchProfile((profiles.active() + 1) % PROFILE_COUNT));
+}
+
+fn play_on_pointer_click(
+    multiplayer: Multiplayer,
+) -> impl FnMut(
+    Trigger<Pointer<Click>>,
+    ResMut<Multiplayer>,
+    ResMut<DailyRun>,
+    ResMut<TutorialRun>,
+    ResMut<RunSeed>,
+    ResMut<NextTransitionedState>,
+) {
+    move |_, mut current, mut daily, mut tutorial, mut seed, mut next_screen| {
+        *current = multiplayer;
+        daily.0 = None;
+        tutorial.0 = false;
+        seed.0 = None;
+        next_screen.set(Screen::Game);
+    }
+}
+
+fn cycle_mode_on_pointer_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut mode: ResMut<GameMode>,
+    progression: Res<Progression>,
+    children_q: Query<&Children>,
+    mut text_q: Query<&mut Text>,
+) {
+    // endless is always unlocked
+    *mode = mode.next();
+    while !progression.mode_unlocked(*mode) {
+        *mode = mode.next();
+    }
+    for child_e in children_q.iter_descendants(trigger.target()) {
+        if let Ok(mut text) = text_q.get_mut(child_e) {
+            text.0 = mode.name().to_string();
+        }
+    }
+}
+
+fn play_daily_on_pointer_click(
+    _trigger: Trigger<Pointer<Click>>,
+    mut multiplayer: ResMut<Multiplayer>,
+    mut daily: ResMut<DailyRun>,
+    mut tutorial: ResMut<TutorialRun>,
+    mut seed: ResMut<RunSeed>,
+    mut next_screen: ResMut<NextTransitionedState>,
+) {
+    let challenge = DailyChallenge::today();
+    *multiplayer = Multiplayer::Off;
+    daily.0 = Some(challenge);
+    tutorial.0 = false;
+    seed.0 = Some(challenge.seed);
+    next_screen.set(Screen::Game);
+}
+
+fn play_tutorial_on_pointer_click(
+    _trigger: Trigger<Pointer<Click>>,
+    mut multiplayer: ResMut<Multiplayer>,
+    mut daily: ResMut<DailyRun>,
+    mut tutorial: ResMut<TutorialRun>,
+    mut seed: ResMut<RunSeed>,
+    mut next_screen: ResMut<NextTransitionedState>,
+) {
+    *multiplayer = Multiplayer::Off;
+    daily.0 = None;
+    tutorial.0 = true;
+    seed.0 = None;
+    next_screen.set(Screen::Game);
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -0,0 +1,84 @@
+//! A screen for spending scrap on unlocks, accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::progression::{Progression, Unlock},
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Unlocks), spawn_unlocks)
+        .add_systems(
+            Update,
+            respawn_unlocks.run_if(in_state(Screen::Unlocks).and(resource_changed::<Progression>)),
+        );
+}
+
+#[derive(Component, Debug)]
+struct UnlocksRoot;
+
+fn unlock_label(unlock: Unlock, progression: &Progression) -> String {
+    let def = unlock.def();
+    let status = if progression.is_equipped(unlock) {
+        "EQUIPPED".to_string()
+    } else if progression.owns(unlock) && def.reward.equippable() {
+        "EQUIP".to_string()
+    } else if progression.owns(unlock) {
+        "OWNED".to_string()
+    } else if let Some(required) = def.requires.filter(|u| !progression.owns(*u)) {
+        format!("NEEDS {}", required.def().name)
+    } else {
+        format!("{} SCRAP", def.cost)
+    };
+    format!("{} - {status}", def.name)
+}
+
+fn spawn_unlocks(mut commands: Commands, progression: Res<Progression>) {
+    commands
+        .spawn((
+            UnlocksRoot,
+            StateScoped(Screen::Unlocks),
+            widget::ui_root("unlocks"),
+            children![
+                widget::header("UNLOCKS"),
+                widget::label(format!("SCRAP: {}", progression.scrap)),
+            ],
+        ))
+        .with_children(|b| {
+            for unlock in Unlock::ALL {
+                b.spawn(widget::button_list_item(
+                    unlock_label(unlock, &progression),
+                    on_unlock_click(unlock),
+                ));
+            }
+            b.spawn(widget::button(
+                "BACK",
+                super::enter_screen_on_pointer_click(Screen::Title),
+            ));
+        });
+}
+
+/// Rebuilt after every purchase, the list is small enough.
+fn respawn_unlocks(
+    mut commands: Commands,
+    root_q: Query<Entity, With<UnlocksRoot>>,
+    progression: Res<Progression>,
+) {
+    for e in &root_q {
+        commands.entity(e).despawn();
+    }
+    spawn_unlocks(commands, progression);
+}
+
+/// Buys the unlock, or toggles it if it's already owned.
+fn on_unlock_click(unlock: Unlock) -> impl FnMut(Trigger<Pointer<Click>>, ResMut<Progression>) {
+    move |_, mut progression| {
+        if progression.owns(unlock) {
+            progression.toggle_equipped(unlock);
+        } else {
+            progression.buy(unlock);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! The screen announcing the winner of a versus match.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{game::versus::VersusResult, theme::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::VersusResults), enter_versus_results);
+}
+
+fn enter_versus_results(mut commands: Commands, result: Res<VersusResult>) {
+    let title = match result.winner {
+        Some(player) => format!("PLAYER {} WINS", player.0 + 1),
+        None => "DRAW".to_string(),
+    };
+    commands.spawn((
+        StateScoped(Screen::VersusResults),
+        widget::ui_root("versus_results"),
+        children![
+            widget::header(title),
+            widget::button(
+                "REMATCH",
+                super::enter_screen_on_pointer_click(Screen::Game)
+            ),
+            widget::button("MENU", super::enter_screen_on_pointer_click(Screen::Title)),
+        ],
+    ));
+}
@@ -0,0 +1,246 @@
+//! Balance simulator, bots play seeded runs back to back and every run is written as a CSV row.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    time::Instant,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        bot::BotPlayers,
+        daily::DailyRun,
+        mode::{GameMode, ModeClock},
+        player::Multiplayer,
+        pool::{EntityPool, PoolStats},
+        rng::RunSeed,
+        score::Score,
+        spawn::enemy::EnemyKind,
+        stats::RunStats,
+        tutorial::TutorialRun,
+    },
+    screen::{Screen, in_game_state},
+};
+
+pub use crate::game::bot::BotStrategy;
+
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub runs: usize,
+    pub strategy: BotStrategy,
+    /// Seed of the first run, the following runs increment it.
+    pub seed: u64,
+    /// Runs are cut short after this time, so a bot that's too good can't stall the simulation.
+    pub max_run_s: f32,
+    /// CSV is written to stdout when `None`.
+    pub output: Option<PathBuf>,
+    /// Turning it off spawns a new entity for every projectile, enemy and particle effect.
+    pub pooling: bool,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            runs: 100,
+            strategy: BotStrategy::default(),
+            seed: 0,
+            max_run_s: 900.,
+            output: None,
+            pooling: true,
+        }
+    }
+}
+
+/// Runs the simulation on top of [`crate::HeadlessAppPlugin`] and exits once all runs are done.
+pub struct SimPlugin(pub SimConfig);
+
+impl Plugin for SimPlugin {
+    fn build(&self, app: &mut App) {
+        let out: Box<dyn Write + Send + Sync> = match &self.0.output {
+            Some(path) => Box::new(BufWriter::new(
+                File::create(path).expect("Failed to create the output file"),
+            )),
+            None => Box::new(std::io::stdout()),
+        };
+        app.insert_resource(Simulation {
+            config: self.0.clone(),
+            finished_runs: 0,
+            timed_out: false,
+            started: Instant::now(),
+            out,
+        })
+        .add_systems(
+            OnEnter(Screen::Title),
+            (write_header, start_next_run).chain(),
+        )
+        .add_systems(Update, end_run_on_timeout.run_if(in_game_state))
+        .add_systems(
+            OnEnter(Screen::GameOver),
+            (write_run, start_next_run).chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct Simulation {
+    config: SimConfig,
+    finished_runs: usize,
+    /// The current run has been cut short by [`SimConfig::max_run_s`].
+    timed_out: bool,
+    /// Wall clock time of the simulation start, logged with the pool stats at the end.
+    started: Instant,
+    out: Box<dyn Write + Send + Sync>,
+}
+
+impl Simulation {
+    fn seed(&self) -> u64 {
+        self.config.seed.wrapping_add(self.finished_runs as u64)
+    }
+}
+
+const RUN_COLUMNS: [&str; 6] = [
+    "run",
+    "seed",
+    "strategy",
+    "survival_s",
+    "score",
+    "timed_out",
+];
+/// Written after the kills of every [`EnemyKind`].
+const STAT_COLUMNS: [&str; 14] = [
+    "kills_gun",
+    "kills_ball",
+    "kills_parry",
+    "shots_fired",
+    "shots_hit",
+    "accuracy",
+    "max_ball_speed",
+    "captures",
+    "cycles",
+    "ammo_spent",
+    "ammo_gained",
+    "hits_enemy",
+    "hits_projectile",
+    "hits_ball",
+];
+
+fn write_header(mut sim: ResMut<Simulation>) -> Result {
+    if sim.finished_runs > 0 {
+        return Ok(());
+    }
+
+    let kills = EnemyKind::ALL
+        .iter()
+        .map(|kind| format!("kills_{}", kind.name().to_lowercase().replace(' ', "_")));
+    let columns: Vec<_> = RUN_COLUMNS
+        .map(String::from)
+        .into_iter()
+        .chain(kills)
+        .chain(STAT_COLUMNS.map(String::from))
+        .collect();
+    writeln!(sim.out, "{}", columns.join(","))?;
+    Ok(())
+}
+
+fn start_next_run(
+    mut sim: ResMut<Simulation>,
+    mut mode: ResMut<GameMode>,
+    mut multiplayer: ResMut<Multiplayer>,
+    mut daily: ResMut<DailyRun>,
+    mut tutorial: ResMut<TutorialRun>,
+    mut bots: ResMut<BotPlayers>,
+    mut seed: ResMut<RunSeed>,
+    mut pool: ResMut<EntityPool>,
+    mut next_state: ResMut<NextState<Screen>>,
+    mut app_exit: EventWriter<AppExit>,
+) -> Result {
+    if sim.finished_runs >= sim.config.runs {
+        sim.out.flush()?;
+        let PoolStats {
+            spawned,
+            reused,
+            released,
+        } = pool.stats;
+        info!(
+            "finished {} runs in {:.1}s with pooling {}: {spawned} entities spawned, {reused} reused, {released} released",
+            sim.finished_runs,
+            sim.started.elapsed().as_secs_f32(),
+            if pool.enabled { "on" } else { "off" },
+        );
+        app_exit.write(AppExit::Success);
+        return Ok(());
+    }
+
+    *mode = GameMode::Endless;
+    *multiplayer = Multiplayer::Off;
+    daily.0 = None;
+    tutorial.0 = false;
+    sim.timed_out = false;
+    bots.0 = Some(sim.config.strategy);
+    seed.0 = Some(sim.seed());
+    pool.enabled = sim.config.pooling;
+    // skip the screen transition
+    next_state.set(Screen::Game);
+    Ok(())
+}
+
+fn end_run_on_timeout(
+    mut stats: ResMut<RunStats>,
+    mut sim: ResMut<Simulation>,
+    clock: Res<ModeClock>,
+    mut next_state: ResMut<NextState<Screen>>,
+) {
+    if !stats.ended && clock.elapsed_s >= sim.config.max_run_s {
+        stats.ended = true;
+        sim.timed_out = true;
+        next_state.set(Screen::GameOver);
+    }
+}
+
+fn write_run(mut sim: ResMut<Simulation>, stats: Res<RunStats>, score: Res<Score>) -> Result {
+    let run = sim.finished_runs;
+    let mut row = vec![
+        run.to_string(),
+        sim.seed().to_string(),
+        sim.config.strategy.name().to_string(),
+        format!("{:.2}", stats.survival_s),
+        score.0.to_string(),
+        sim.timed_out.to_string(),
+    ];
+    row.extend(stats.kills.iter().map(usize::to_string));
+    row.extend(
+        [
+            stats.gun_kills,
+            stats.ball_kills,
+            stats.reflected_kills,
+            stats.shots_fired,
+            stats.shots_hit,
+        ]
+        .map(|v| v.to_string()),
+    );
+    row.push(format!("{:.3}", stats.accuracy()));
+    row.push(format!("{:.0}", stats.max_ball_speed));
+    row.extend(
+        [
+            stats.captures,
+            stats.cycles,
+            stats.ammo_spent,
+            stats.ammo_gained,
+            stats.enemy_damage,
+            stats.projectile_damage,
+            stats.ball_damage,
+        ]
+        .map(|v| v.to_string()),
+    );
+    writeln!(sim.out, "{}", row.join(","))?;
+
+    sim.finished_runs += 1;
+    info!(
+        "finished run {}/{} after {:.0}s",
+        sim.finished_runs, sim.config.runs, stats.survival_s
+    );
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! Tiny key-value storage for data that should survive restarts.
+//! Native builds keep a file per key in the user's data directory, web builds use local storage
+//! and tests keep everything in memory.
+//!
+//! Every save keeps the previous value as a backup, in case the new one turns out unreadable.
+
+#[cfg(not(test))]
+use bevy::log::warn;
+
+#[cfg(not(any(target_family = "wasm", test)))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(data_dir()?.join(key)).ok()
+}
+
+#[cfg(not(any(target_family = "wasm", test)))]
+pub fn load_backup(key: &str) -> Option<String> {
+    std::fs::read_to_string(data_dir()?.join(backup_key(key))).ok()
+}
+
+/// Writes a temporary file first and renames it over the old one,
+/// so a crash mid-write never leaves a half written file behind.
+#[cfg(not(any(target_family = "wasm", test)))]
+pub fn save(key: &str, value: &str) {
+    let Some(dir) = data_dir() else {
+        warn!(key, "no data directory to save to");
+        return;
+    };
+    let path = dir.join(key);
+    let tmp_path = dir.join(format!("{key}.tmp"));
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&tmp_path, value))
+        .and_then(|_| {
+            if path.exists() {
+                std::fs::copy(&path, dir.join(backup_key(key)))?;
+            }
+            std::fs::rename(&tmp_path, &path)
+        });
+    if let Err(err) = result {
+        warn!(key, ?err, "failed to save");
+    }
+}
+
+/// Keeps an unreadable save next to it, so it isn't lost once the key is saved over.
+#[cfg(not(any(target_family = "wasm", test)))]
+pub fn quarantine(key: &str, suffix: &str, value: &str) {
+    let Some(dir) = data_dir() else {
+        warn!(key, "no data directory to quarantine the save to");
+        return;
+    };
+    if let Err(err) = std::fs::write(dir.join(quarantine_key(key, suffix)), value) {
+        warn!(key, ?err, "failed to quarantine the save");
+    }
+}
+
+#[cfg(not(any(target_family = "wasm", test)))]
+fn data_dir() -> Option<std::path::PathBuf> {
+    use std::{env::var_os, path::PathBuf};
+
+    let base = var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+    Some(base.join("porcle"))
+}
+
+#[cfg(all(target_family = "wasm", not(test)))]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(&storage_key(key)).ok()?
+}
+
+#[cfg(all(target_family = "wasm", not(test)))]
+pub fn load_backup(key: &str) -> Option<String> {
+    load(&backup_key(key))
+}
+
+#[cfg(all(target_family = "wasm", not(test)))]
+pub fn save(key: &str, value: &str) {
+    let saved = local_storage().is_some_and(|storage| {
+        if let Ok(Some(prev)) = storage.get_item(&storage_key(key)) {
+            let _ = storage.set_item(&storage_key(&backup_key(key)), &prev);
+        }
+        storage.set_item(&storage_key(key), value).is_ok()
+    });
+    if !saved {
+        warn!(key, "failed to save");
+    }
+}
+
+#[cfg(all(target_family = "wasm", not(test)))]
+pub fn quarantine(key: &str, suffix: &str, value: &str) {
+    let saved = local_storage().is_some_and(|storage| {
+        storage
+            .set_item(&storage_key(&quarantine_key(key, suffix)), value)
+            .is_ok()
+    });
+    if !saved {
+        warn!(key, "failed to quarantine the save");
+    }
+}
+
+#[cfg(all(target_family = "wasm", not(test)))]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(all(target_family = "wasm", not(test)))]
+fn storage_key(key: &str) -> String {
+    format!("porcle.{key}")
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Every test runs on its own thread, and so gets its own storage.
+    static MEMORY: std::cell::RefCell<std::collections::HashMap<String, String>> =
+        Default::default();
+}
+
+#[cfg(test)]
+pub fn load(key: &str) -> Option<String> {
+    MEMORY.with_borrow(|memory| memory.get(key).cloned())
+}
+
+#[cfg(test)]
+pub fn load_backup(key: &str) -> Option<String> {
+    load(&backup_key(key))
+}
+
+#[cfg(test)]
+pub fn save(key: &str, value: &str) {
+    MEMORY.with_borrow_mut(|memory| {
+        if let Some(prev) = memory.insert(key.to_string(), value.to_string()) {
+            memory.insert(backup_key(key), prev);
+        }
+    });
+}
+
+#[cfg(test)]
+pub fn quarantine(key: &str, suffix: &str, value: &str) {
+    MEMORY.with_borrow_mut(|memory| memory.insert(quarantine_key(key, suffix), value.to_string()));
+}
+
+fn backup_key(key: &str) -> String {
+    format!("{key}.bak")
+}
+
+fn quarantine_key(key: &str, suffix: &str) -> String {
+    format!("{key}.{suffix}")
+}
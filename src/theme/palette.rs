@@ -19,11 +19,14 @@ pub const COL_TRANSITION_1: Color = Color::srgb(0.09, 0.43, 0.48);
 pub const COL_TRANSITION_2: Color = Color::srgb(0.15, 0.30, 0.44);
 pub const COL_TRANSITION_3: Color = Color::srgb(0.15, 0.14, 0.27);
 pub const COL_PADDLE: Color = Color::srgb(0.27, 0.57, 0.65);
+pub const COL_PADDLE_2: Color = Color::srgb(0.95, 0.65, 0.37);
 pub const COL_PADDLE_TRACKS: Color = Color::srgb(0.15, 0.30, 0.44);
 pub const COL_BULLET: Color = Color::srgb(0.99, 0.96, 0.38);
 pub const COL_PADDLE_REFLECT: Color = Color::srgb(0.09, 0.43, 0.48);
 pub const COL_PADDLE_CAPTURED: Color = COL_GEARS_DISABLED;
 pub const COL_PADDLE_CAPTURE: Color = COL_BALL;
+pub const COL_PADDLE_PARRY: Color = COL_BALL_FAST;
+pub const COL_PADDLE_PARRY_MISS: Color = COL_ENEMY_PROJECTILE;
 pub const COL_BALL: Color = Color::srgb(0.55, 0.85, 0.58);
 pub const COL_BALL_FAST: Color = Color::srgb(0.85, 0.89, 0.99);
 pub const COL_GEARS: Color = Color::srgb(0.15, 0.30, 0.44);
@@ -34,6 +37,7 @@ pub const COL_AMMO_OUT: Color = Color::srgb(0.15, 0.14, 0.27);
 pub const COL_ENEMY: Color = Color::srgb(0.79, 0.38, 0.68);
 pub const COL_ENEMY_FLASH: Color = COL_BALL_FAST;
 pub const COL_ENEMY_PROJECTILE: Color = Color::srgb(0.92, 0.36, 0.75);
+pub const COL_PICKUP_SHIELD: Color = COL_TRANSITION_1;
 
 // todo: try to move a couple of colors around
 // and use colors from
@@ -84,6 +84,28 @@ where
             justify_content: JustifyContent::Center,
             ..default()
         },),
+        40.0,
+    )
+}
+
+/// A wide and short button for long lists of options.
+pub fn button_list_item<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
+where
+    E: Event,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base(
+        text,
+        action,
+        Node {
+            width: Px(560.0),
+            height: Px(36.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        22.0,
     )
 }
 
@@ -104,6 +126,7 @@ where
             justify_content: JustifyContent::Center,
             ..default()
         },
+        40.0,
     )
 }
 
@@ -112,6 +135,7 @@ fn button_base<E, B, M, I>(
     text: impl Into<String>,
     action: I,
     button_bundle: impl Bundle,
+    font_size: f32,
 ) -> impl Bundle
 where
     E: Event,
@@ -145,7 +169,7 @@ where
                     children![(
                         Name::new("Button Text"),
                         Text(text),
-                        TextFont::from_font_size(40.0),
+                        TextFont::from_font_size(font_size),
                         TextColor(BUTTON_TEXT),
                     )],
                 ))